@@ -0,0 +1,28 @@
+//! Generates the `register_days!` call from the `pub mod dayNN;` lines in src/lib.rs, so
+//! that declaring a day's module is all it takes to register it.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let lib_file = Path::new("src").join("lib.rs");
+    println!("cargo:rerun-if-changed={}", lib_file.display());
+    let lib = fs::read_to_string(&lib_file).expect("Unable to read src/lib.rs");
+
+    let modules: Vec<_> = lib
+        .lines()
+        .filter_map(|line| line.strip_prefix("pub mod ")?.strip_suffix(';'))
+        .filter(|name| {
+            name.strip_prefix("day")
+                .is_some_and(|day| day.len() == 2 && day.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .collect();
+
+    let out_file = Path::new(&env::var("OUT_DIR").unwrap()).join("days.rs");
+    fs::write(
+        out_file,
+        format!("register_days! {{ {} }}\n", modules.join(", ")),
+    )
+    .expect("Unable to write days.rs");
+}
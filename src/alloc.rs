@@ -0,0 +1,182 @@
+//! Counting global allocator, installed in tests and with the `alloc-stats` feature.
+//!
+//! Counters are kept per thread, so [`measure`] only sees the allocations made by the code
+//! it runs, whatever other threads are doing.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Whether the counting allocator is installed; without it [`measure`] reports nothing.
+pub const ENABLED: bool = cfg!(any(test, feature = "alloc-stats"));
+
+/// Allocations made while running a piece of code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: u64,
+    /// Total bytes requested, including memory freed again.
+    pub bytes: u64,
+    /// Highest number of bytes held at once, above what was held at the start.
+    pub peak: u64,
+}
+
+struct Counters {
+    allocations: Cell<u64>,
+    bytes: Cell<u64>,
+    live: Cell<u64>,
+    peak: Cell<u64>,
+    limit: Cell<u64>,
+}
+
+thread_local! {
+    static COUNTERS: Counters = const {
+        Counters {
+            allocations: Cell::new(0),
+            bytes: Cell::new(0),
+            live: Cell::new(0),
+            peak: Cell::new(0),
+            limit: Cell::new(u64::MAX),
+        }
+    };
+}
+
+/// Number of threads stalled for going past their limit.
+static STALLED: AtomicUsize = AtomicUsize::new(0);
+
+fn allocated(size: usize) {
+    let _ = COUNTERS.try_with(|c| {
+        c.allocations.set(c.allocations.get() + 1);
+        c.bytes.set(c.bytes.get() + size as u64);
+        let live = c.live.get() + size as u64;
+        c.live.set(live);
+        c.peak.set(c.peak.get().max(live));
+        if live > c.limit.get() {
+            STALLED.fetch_add(1, Ordering::SeqCst);
+            loop {
+                thread::sleep(Duration::from_secs(3600));
+            }
+        }
+    });
+}
+
+fn freed(size: usize) {
+    // Memory may be freed by another thread than the one that allocated it.
+    let _ = COUNTERS.try_with(|c| c.live.set(c.live.get().saturating_sub(size as u64)));
+}
+
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        allocated(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        allocated(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        freed(layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        freed(layout.size());
+        allocated(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(any(test, feature = "alloc-stats"))]
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+/// Runs `f` and counts the allocations it makes on this thread, if the counting allocator
+/// is installed. Calls do not nest: an inner call resets the peak of an outer one.
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Option<AllocStats>) {
+    if !ENABLED {
+        return (f(), None);
+    }
+    let (allocations, bytes, live) = COUNTERS.with(|c| {
+        c.peak.set(c.live.get());
+        (c.allocations.get(), c.bytes.get(), c.live.get())
+    });
+    let result = f();
+    let stats = COUNTERS.with(|c| AllocStats {
+        allocations: c.allocations.get() - allocations,
+        bytes: c.bytes.get() - bytes,
+        peak: c.peak.get() - live,
+    });
+    (result, Some(stats))
+}
+
+/// Stalls this thread forever once it holds more than `bytes` on top of what it holds
+/// now, so that runaway allocation can be detected with [`stalled`] instead of exhausting
+/// memory.
+pub fn limit_thread(bytes: u64) {
+    COUNTERS.with(|c| c.limit.set(c.live.get().saturating_add(bytes)));
+}
+
+/// Number of threads stalled by [`limit_thread`] so far.
+pub fn stalled() -> usize {
+    STALLED.load(Ordering::SeqCst)
+}
+
+/// Formats a byte count with a binary unit, such as `1.5 KiB`.
+pub struct Bytes(pub u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        if self.0 < 1024 {
+            return f.pad(&format!("{} B", self.0));
+        }
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        f.pad(&format!("{:.1} {}", value, UNITS[unit]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+
+    #[test]
+    fn test_measure() {
+        let (_, stats) = measure(|| black_box(1 + 1));
+        assert_eq!(stats, Some(AllocStats::default()));
+
+        let (v, stats) = measure(|| {
+            let kept = black_box(vec![0u8; 1000]);
+            drop(black_box(vec![0u8; 3000]));
+            kept
+        });
+        let stats = stats.unwrap();
+        assert_eq!(v.len(), 1000);
+        assert_eq!(stats.allocations, 2);
+        assert_eq!(stats.bytes, 4000);
+        assert_eq!(stats.peak, 4000);
+
+        let (_, stats) = measure(|| drop(v));
+        assert_eq!(stats.unwrap().peak, 0);
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(Bytes(0).to_string(), "0 B");
+        assert_eq!(Bytes(1023).to_string(), "1023 B");
+        assert_eq!(Bytes(1536).to_string(), "1.5 KiB");
+        assert_eq!(Bytes(3 << 30).to_string(), "3.0 GiB");
+        assert_eq!(format!("{:>8}", Bytes(10)), "    10 B");
+    }
+}
@@ -0,0 +1,130 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+///
+/// Solvers return any type that converts into it: integers, strings, `Option`s of those
+/// (`None` meaning the input has no solution) or an `Answer` itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    /// An integer too large for `Int`, as its decimal digits.
+    Big(String),
+    /// A non-numeric answer, such as a comma-joined list.
+    Text(String),
+    /// The input has no answer, for instance no entries adding up to the target.
+    NoSolution,
+    /// The part has not been solved yet.
+    NotImplemented,
+}
+
+impl Answer {
+    /// Returns the integer written as `digits`, with an optional leading `-`, or `None`
+    /// if it is not a decimal integer. Integers that fit are stored as `Int`.
+    pub fn big(digits: &str) -> Option<Self> {
+        let unsigned = digits.strip_prefix('-').unwrap_or(digits);
+        if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(match digits.parse() {
+            Ok(n) => Self::Int(n),
+            Err(_) => Self::Big(digits.to_string()),
+        })
+    }
+
+    /// Whether there is an actual answer, one that can be checked or submitted.
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Int(_) | Self::Big(_) | Self::Text(_))
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{}", n),
+            Self::Big(digits) | Self::Text(digits) => f.write_str(digits),
+            Self::NoSolution => f.write_str("no solution"),
+            Self::NotImplemented => f.write_str("not implemented"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($int:ty),*) => {
+        $(impl From<$int> for Answer {
+            fn from(n: $int) -> Self {
+                Self::Int(n as i128)
+            }
+        })*
+    };
+}
+
+answer_from_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        match i128::try_from(n) {
+            Ok(n) => Self::Int(n),
+            Err(_) => Self::Big(n.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::Text(s.to_string())
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(answer: Option<T>) -> Self {
+        answer.map_or(Self::NoSolution, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Answer::from(42usize), Answer::Int(42));
+        assert_eq!(Answer::from(-7i64), Answer::Int(-7));
+        assert_eq!(
+            Answer::from(u128::MAX),
+            Answer::Big("340282366920938463463374607431768211455".to_string())
+        );
+        assert_eq!(
+            Answer::from("mxmxvkd,sqjhc"),
+            Answer::Text("mxmxvkd,sqjhc".to_string())
+        );
+        assert_eq!(Answer::from(Some(3)), Answer::Int(3));
+        assert_eq!(Answer::from(None::<i32>), Answer::NoSolution);
+    }
+
+    #[test]
+    fn test_big() {
+        let digits = "-1000000000000000000000000000000000000000000";
+        assert_eq!(Answer::big(digits), Some(Answer::Big(digits.to_string())));
+        assert_eq!(Answer::big("-12"), Some(Answer::Int(-12)));
+        assert_eq!(Answer::big(""), None);
+        assert_eq!(Answer::big("-"), None);
+        assert_eq!(Answer::big("1e9"), None);
+        assert_eq!(Answer::big(digits).unwrap().to_string(), digits);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::Int(-5).to_string(), "-5");
+        assert_eq!(Answer::NoSolution.to_string(), "no solution");
+        assert_eq!(Answer::NotImplemented.to_string(), "not implemented");
+        assert!(Answer::Text(String::new()).is_value());
+        assert!(!Answer::NotImplemented.is_value());
+    }
+}
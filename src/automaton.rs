@@ -0,0 +1,297 @@
+//! Cellular automata: boards whose cells all change at once, each according to its
+//! neighbours.
+//!
+//! A [`Rule`] picks the neighbours of a cell and its next state, and a [`Board`] applies a
+//! rule to every cell. [`Automaton`] runs them, keeping two boards and swapping them each
+//! round, so no round allocates once both have grown to size. It stops at a fixed point,
+//! after a number of rounds or, with [`Automaton::find_cycle`], on a board seen before.
+
+use crate::grid::{Grid, Point, SparseGrid};
+use std::iter::once;
+use std::mem;
+
+/// How each cell of a board `B` changes from one round to the next.
+pub trait Rule<B: Board> {
+    /// The cells whose states decide the next state of the cell at `pos`, with their
+    /// positions.
+    fn neighbours<'a>(
+        &'a self,
+        board: &'a B,
+        pos: B::Pos,
+    ) -> impl Iterator<Item = (B::Pos, &'a B::Cell)> + 'a
+    where
+        B::Cell: 'a;
+
+    /// The next state of `cell`, given its neighbours.
+    fn new_state<'a>(
+        &self,
+        cell: &B::Cell,
+        neighbours: impl Iterator<Item = (B::Pos, &'a B::Cell)>,
+    ) -> B::Cell
+    where
+        B::Cell: 'a;
+}
+
+/// Cells laid out in some space, all updated together.
+pub trait Board: Clone + PartialEq {
+    type Pos: Copy;
+    type Cell;
+
+    /// Overwrites `next` with this board after one round of `rule`, reusing its storage.
+    /// `next` is always a board produced by cloning or stepping this one.
+    fn step_into<R: Rule<Self>>(&self, rule: &R, next: &mut Self);
+}
+
+impl<T: Clone + PartialEq> Board for Grid<T> {
+    type Pos = Point;
+    type Cell = T;
+
+    fn step_into<R: Rule<Self>>(&self, rule: &R, next: &mut Self) {
+        self.map_into(next, |p, cell| {
+            rule.new_state(cell, rule.neighbours(self, p))
+        });
+    }
+}
+
+/// Only the stored cells and their neighbours are updated, so the rule must leave a default
+/// cell surrounded by defaults alone, and a cell must be a neighbour of its neighbours.
+impl<T: Clone + PartialEq> Board for SparseGrid<T> {
+    type Pos = Point;
+    type Cell = T;
+
+    fn step_into<R: Rule<Self>>(&self, rule: &R, next: &mut Self) {
+        next.clear();
+        for (p, _) in self.iter() {
+            for q in once(p).chain(rule.neighbours(self, p).map(|(q, _)| q)) {
+                next.set(q, rule.new_state(self.get(q), rule.neighbours(self, q)));
+            }
+        }
+    }
+}
+
+/// How a run of an [`Automaton`] ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The board stopped changing at this round.
+    Stable { round: usize },
+    /// The board at `start` comes back every `period` rounds, where `period` is at least 2.
+    Cycle { start: usize, period: usize },
+    /// The round limit was reached first.
+    Limit,
+}
+
+/// Applies one round of `rule` to `board`, using `scratch` as the second buffer, and
+/// returns whether anything changed.
+fn advance<B: Board, R: Rule<B>>(rule: &R, board: &mut B, scratch: &mut B) -> bool {
+    board.step_into(rule, scratch);
+    mem::swap(board, scratch);
+    board != scratch
+}
+
+/// A board and the rule it evolves by.
+pub struct Automaton<B, R> {
+    board: B,
+    next: B,
+    rule: R,
+    round: usize,
+}
+
+impl<B: Board, R: Rule<B>> Automaton<B, R> {
+    pub fn new(board: B, rule: R) -> Self {
+        Self {
+            next: board.clone(),
+            board,
+            rule,
+            round: 0,
+        }
+    }
+
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    pub fn into_board(self) -> B {
+        self.board
+    }
+
+    /// The number of rounds the current board is from the starting one.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Runs one round and returns whether anything changed.
+    pub fn step(&mut self) -> bool {
+        self.round += 1;
+        advance(&self.rule, &mut self.board, &mut self.next)
+    }
+
+    /// Runs until the board stops changing, or for at most `limit` more rounds. A stable
+    /// board is left at the first round it appeared.
+    pub fn run(&mut self, limit: Option<usize>) -> Outcome {
+        let mut remaining = limit;
+        loop {
+            if remaining == Some(0) {
+                return Outcome::Limit;
+            }
+            remaining = remaining.map(|n| n - 1);
+            if !self.step() {
+                self.round -= 1;
+                return Outcome::Stable { round: self.round };
+            }
+        }
+    }
+
+    /// Runs until a board repeats, or for at most `limit` more rounds, using Brent's
+    /// algorithm. A repeating board is left at the first round of the cycle, so the board
+    /// at any later round `n` is the one `(n - start) % period` rounds on.
+    pub fn find_cycle(&mut self, limit: Option<usize>) -> Outcome {
+        let (start, start_round) = (self.board.clone(), self.round);
+        let mut remaining = limit;
+        let mut tortoise = self.board.clone();
+        let (mut power, mut period) = (1, 0);
+        loop {
+            if remaining == Some(0) {
+                return Outcome::Limit;
+            }
+            remaining = remaining.map(|n| n - 1);
+            self.step();
+            period += 1;
+            if self.board == tortoise {
+                break;
+            }
+            if period == power {
+                tortoise.clone_from(&self.board);
+                power *= 2;
+                period = 0;
+            }
+        }
+
+        // Walk two boards `period` rounds apart from the start until they meet.
+        let mut hare = start.clone();
+        for _ in 0..period {
+            advance(&self.rule, &mut hare, &mut self.next);
+        }
+        self.board = start;
+        self.round = start_round;
+        while self.board != hare {
+            self.step();
+            advance(&self.rule, &mut hare, &mut self.next);
+        }
+        match period {
+            1 => Outcome::Stable { round: self.round },
+            period => Outcome::Cycle {
+                start: self.round,
+                period,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Cells, Neighbourhood};
+
+    /// Conway's Game of Life.
+    struct Life;
+
+    impl<B: Board<Pos = Point, Cell = bool> + Cells<Cell = bool>> Rule<B> for Life {
+        fn neighbours<'a>(
+            &'a self,
+            board: &'a B,
+            pos: Point,
+        ) -> impl Iterator<Item = (Point, &'a bool)> + 'a
+        where
+            <B as Board>::Cell: 'a,
+        {
+            board.neighbours(pos, Neighbourhood::Eight)
+        }
+
+        fn new_state<'a>(
+            &self,
+            &alive: &bool,
+            neighbours: impl Iterator<Item = (Point, &'a bool)>,
+        ) -> bool
+        where
+            <B as Board>::Cell: 'a,
+        {
+            matches!(
+                (alive, neighbours.filter(|(_, &n)| n).count()),
+                (true, 2) | (_, 3)
+            )
+        }
+    }
+
+    fn parse(s: &str) -> Grid<bool> {
+        Grid::parse(s.as_bytes(), |ch| Ok(ch == '#')).unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        let block = parse("....\n.##.\n.##.\n....\n");
+        let mut automaton = Automaton::new(block.clone(), Life);
+        assert_eq!(automaton.run(None), Outcome::Stable { round: 0 });
+        assert_eq!(automaton.board(), &block);
+
+        let mut automaton = Automaton::new(parse(".....\n..#..\n..#..\n..#..\n.....\n"), Life);
+        assert_eq!(automaton.run(Some(5)), Outcome::Limit);
+        assert_eq!(automaton.round(), 5);
+
+        // Three cells in an L become a block.
+        let mut automaton = Automaton::new(parse("....\n.#..\n.##.\n....\n"), Life);
+        assert_eq!(automaton.run(None), Outcome::Stable { round: 1 });
+        assert_eq!(automaton.into_board(), block);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        // A blinker that starts in step with a block that has not formed yet.
+        let grid = parse(
+            "........
+.#......
+.##.....
+........
+........
+....###.
+........
+........
+",
+        );
+        let mut automaton = Automaton::new(grid.clone(), Life);
+        assert_eq!(
+            automaton.find_cycle(None),
+            Outcome::Cycle {
+                start: 1,
+                period: 2
+            }
+        );
+        assert_eq!(automaton.round(), 1);
+        let cycle_start = automaton.board().clone();
+        automaton.step();
+        assert_ne!(automaton.board(), &cycle_start);
+        automaton.step();
+        assert_eq!(automaton.board(), &cycle_start);
+
+        let mut automaton = Automaton::new(grid, Life);
+        assert_eq!(automaton.find_cycle(Some(1)), Outcome::Limit);
+
+        let mut automaton = Automaton::new(parse("#\n"), Life);
+        assert_eq!(automaton.find_cycle(None), Outcome::Stable { round: 1 });
+    }
+
+    #[test]
+    fn test_sparse() {
+        // A glider never repeats a board, but comes back moved by (1, 1) every 4 rounds.
+        let glider = SparseGrid::from_grid(&parse(".#.\n..#\n###\n"), false);
+        let mut automaton = Automaton::new(glider.clone(), Life);
+        for _ in 0..4 {
+            automaton.step();
+        }
+        let mut moved = SparseGrid::new(false);
+        for (p, _) in glider.iter() {
+            moved.set((p.0 + 1, p.1 + 1), true);
+        }
+        assert_eq!(automaton.board(), &moved);
+        assert_eq!(automaton.find_cycle(Some(100)), Outcome::Limit);
+    }
+}
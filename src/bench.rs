@@ -0,0 +1,262 @@
+use crate::alloc::{self, AllocStats};
+use crate::error::Error;
+use crate::input::{self, InputSource};
+use crate::solver::Solver;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::hint::black_box;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const BASELINE_FILE: &str = "bench_baseline.txt";
+
+pub struct BenchOptions {
+    pub iterations: usize,
+    pub warmup: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            warmup: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Parse,
+    First,
+    Second,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::First, Phase::Second];
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::First => "part1",
+            Self::Second => "part2",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|phase| phase.key() == key)
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Parse => "parse",
+            Self::First => "part 1",
+            Self::Second => "part 2",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+        Self {
+            min: samples[0],
+            median,
+            max: samples[n - 1],
+        }
+    }
+}
+
+pub struct DayBench {
+    pub day: i32,
+    pub phases: Vec<(Phase, Stats)>,
+    /// Allocations of one untimed run of each phase, when they are counted.
+    pub allocs: Vec<(Phase, AllocStats)>,
+}
+
+/// Runs `f` `options.warmup` times untimed, then `options.iterations` times timed.
+pub fn measure<T, F: FnMut() -> T>(options: &BenchOptions, mut f: F) -> Stats {
+    for _ in 0..options.warmup {
+        black_box(f());
+    }
+    let samples = (0..options.iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    Stats::from_samples(samples)
+}
+
+/// Benchmarks each phase of `solver` separately against the input from `source`, solving
+/// with `params`.
+///
+/// The input is read into memory once so that the parse timings do not include disk I/O.
+pub fn bench<S: Solver>(
+    solver: &S,
+    options: &BenchOptions,
+    source: &InputSource,
+    params: &S::Params,
+) -> Result<DayBench, Error> {
+    let raw_input = source.read(&solver.input_file())?;
+    let parse = |raw_input: &[u8]| {
+        solver
+            .parse_input(raw_input)
+            .map_err(|e| e.for_day(solver.get_day()))
+    };
+    let input = parse(&raw_input)?;
+
+    let phases = vec![
+        (Phase::Parse, measure(options, || parse(&raw_input))),
+        (
+            Phase::First,
+            measure(options, || solver.solve_first(&input, params)),
+        ),
+        (
+            Phase::Second,
+            measure(options, || solver.solve_second(&input, params)),
+        ),
+    ];
+    let allocs = [
+        (Phase::Parse, alloc::measure(|| parse(&raw_input)).1),
+        (
+            Phase::First,
+            alloc::measure(|| solver.solve_first(&input, params)).1,
+        ),
+        (
+            Phase::Second,
+            alloc::measure(|| solver.solve_second(&input, params)).1,
+        ),
+    ]
+    .iter()
+    .filter_map(|&(phase, stats)| Some((phase, stats?)))
+    .collect();
+    Ok(DayBench {
+        day: solver.get_day(),
+        phases,
+        allocs,
+    })
+}
+
+/// Median timings from an earlier run, keyed by day and phase.
+#[derive(Default)]
+pub struct Baseline {
+    medians: BTreeMap<(i32, Phase), Duration>,
+}
+
+impl Baseline {
+    /// Loads a baseline file of `<day> <phase> <median nanoseconds>` lines.
+    pub fn load<P: AsRef<Path>>(p: P) -> io::Result<Self> {
+        let medians = input::load_lines(p, "baseline entry", |line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields[..] {
+                [day, phase, nanos] => Some((
+                    (day.parse().ok()?, Phase::from_key(phase)?),
+                    Duration::from_nanos(nanos.parse().ok()?),
+                )),
+                _ => None,
+            }
+        })?;
+        Ok(Self {
+            medians: medians.into_iter().collect(),
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, p: P) -> io::Result<()> {
+        let content: String = self
+            .medians
+            .iter()
+            .map(|((day, phase), median)| {
+                format!("{} {} {}\n", day, phase.key(), median.as_nanos())
+            })
+            .collect();
+        fs::write(p, content)
+    }
+
+    pub fn get(&self, day: i32, phase: Phase) -> Option<Duration> {
+        self.medians.get(&(day, phase)).copied()
+    }
+
+    pub fn record(&mut self, bench: &DayBench) {
+        for (phase, stats) in bench.phases.iter() {
+            self.medians.insert((bench.day, *phase), stats.median);
+        }
+    }
+
+    /// Returns the relative slowdown of `median` against the baseline when it is past
+    /// `threshold` (0.1 for 10%).
+    pub fn regression(
+        &self,
+        day: i32,
+        phase: Phase,
+        median: Duration,
+        threshold: f64,
+    ) -> Option<f64> {
+        let base = self.get(day, phase)?.as_secs_f64();
+        let change = median.as_secs_f64() / base - 1.0;
+        if base > 0.0 && change > threshold {
+            Some(change)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(vec![ms(5), ms(1), ms(3)]);
+        assert_eq!((stats.min, stats.median, stats.max), (ms(1), ms(3), ms(5)));
+        let stats = Stats::from_samples(vec![ms(4), ms(1), ms(2), ms(8)]);
+        assert_eq!(stats.median, ms(3));
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let path = std::env::temp_dir().join(format!("aoc2020_baseline_{}", std::process::id()));
+        let stats = Stats::from_samples(vec![ms(10)]);
+        let mut baseline = Baseline::default();
+        baseline.record(&DayBench {
+            day: 15,
+            phases: vec![(Phase::Parse, stats), (Phase::Second, stats)],
+            allocs: Vec::new(),
+        });
+        baseline.save(&path).unwrap();
+
+        let loaded = Baseline::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get(15, Phase::Second), Some(ms(10)));
+        assert_eq!(loaded.get(15, Phase::First), None);
+        assert_eq!(loaded.regression(15, Phase::Second, ms(10), 0.1), None);
+        assert_eq!(
+            loaded
+                .regression(15, Phase::Second, ms(12), 0.1)
+                .map(|c| (c * 100.0).round()),
+            Some(20.0)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+/// Command line arguments split into positionals and `--name [value]` options.
+pub struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// Splits `args`; options listed in `value_options` consume the next argument
+    /// as their value unless it was given as `--name=value`.
+    pub fn parse<I>(args: I, value_options: &[&str]) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(option) => match option.split_once('=') {
+                    Some((name, value)) => {
+                        options.push((name.to_string(), Some(value.to_string())))
+                    }
+                    None if value_options.contains(&option) => {
+                        let value = args
+                            .next()
+                            .ok_or_else(|| format!("Missing value for --{}", option))?;
+                        options.push((option.to_string(), Some(value)));
+                    }
+                    None => options.push((option.to_string(), None)),
+                },
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    pub fn positional(&self, ind: usize) -> Option<&str> {
+        self.positional.get(ind).map(String::as_str)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    /// Returns the last value given for `--name`, parsed as `T`.
+    pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| {
+                let value = value
+                    .as_deref()
+                    .ok_or_else(|| format!("Missing value for --{}", name))?;
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid value for --{}: {}", name, value))
+            })
+            .transpose()
+    }
+
+    /// Returns every value given for `--name`, in order.
+    pub fn values(&self, name: &str) -> Result<Vec<&str>, String> {
+        self.options
+            .iter()
+            .filter(|(option, _)| option == name)
+            .map(|(_, value)| {
+                value
+                    .as_deref()
+                    .ok_or_else(|| format!("Missing value for --{}", name))
+            })
+            .collect()
+    }
+
+    /// Rejects options that are not in `known`.
+    pub fn check_options(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .options
+            .iter()
+            .find(|(option, _)| !known.contains(&&option[..]))
+        {
+            Some((option, _)) => Err(format!("Unknown option --{}", option)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = Args::parse(
+            args("bench 15 --iterations 3 --save --warmup=2"),
+            &["iterations"],
+        )
+        .unwrap();
+        assert_eq!(parsed.positional(0), Some("bench"));
+        assert_eq!(parsed.positional(1), Some("15"));
+        assert_eq!(parsed.positional(2), None);
+        assert!(parsed.flag("save"));
+        assert_eq!(parsed.value::<usize>("iterations"), Ok(Some(3)));
+        assert_eq!(parsed.value::<usize>("warmup"), Ok(Some(2)));
+        assert_eq!(parsed.value::<usize>("threshold"), Ok(None));
+        assert!(parsed.value::<usize>("save").is_err());
+        assert_eq!(parsed.values("warmup"), Ok(vec!["2"]));
+        assert_eq!(parsed.values("threshold"), Ok(vec![]));
+        assert!(parsed.check_options(&["iterations", "save"]).is_err());
+    }
+
+    #[test]
+    fn test_missing_value() {
+        assert!(Args::parse(args("bench --iterations"), &["iterations"]).is_err());
+    }
+}
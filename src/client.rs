@@ -0,0 +1,346 @@
+use crate::input;
+use crate::solver::Part;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const CONFIG_FILE: &str = "aoc.conf";
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const SESSION_VAR: &str = "AOC_SESSION";
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+pub const YEAR: i32 = 2020;
+const USER_AGENT: &str = "adventofcode2020 client";
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the puzzle website is and how to log in to it, read from `key: value` lines.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Config {
+    pub session: Option<String>,
+    pub base_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Splits a `key: value` line.
+    fn parse_line(line: &str) -> Option<(String, String)> {
+        let (key, value) = line.split_once(':')?;
+        Some((key.trim().to_string(), value.trim().to_string()))
+    }
+
+    fn set(&mut self, key: &str, value: String) -> Result<(), String> {
+        match key {
+            "session" => self.session = Some(value),
+            "base_url" => self.base_url = value,
+            key => return Err(format!("unknown config key {:?}", key)),
+        }
+        Ok(())
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let (key, value) =
+                Self::parse_line(line).ok_or_else(|| format!("invalid config line {:?}", line))?;
+            config.set(&key, value)?;
+        }
+        Ok(config)
+    }
+
+    pub fn load<P: AsRef<Path>>(p: P) -> Result<Self, String> {
+        let p = p.as_ref();
+        let in_file = |e: String| format!("{}: {}", p.display(), e);
+        let mut config = Self::default();
+        for (key, value) in input::load_lines(p, "config line", Self::parse_line)
+            .map_err(|e| in_file(e.to_string()))?
+        {
+            config.set(&key, value).map_err(in_file)?;
+        }
+        Ok(config)
+    }
+
+    /// Overrides the session token and base URL with the `AOC_SESSION` and `AOC_BASE_URL`
+    /// variables, as returned by `var`.
+    pub fn with_env<F: Fn(&str) -> Option<String>>(mut self, var: F) -> Self {
+        if let Some(session) = var(SESSION_VAR) {
+            self.session = Some(session);
+        }
+        if let Some(base_url) = var(BASE_URL_VAR) {
+            self.base_url = base_url;
+        }
+        self
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// When the puzzle of `day` unlocks: midnight US Eastern time (05:00 UTC) on that day of
+/// December.
+pub fn unlock_time(day: i32) -> SystemTime {
+    let days = days_from_civil(i64::from(YEAR), 12, i64::from(day));
+    UNIX_EPOCH + Duration::from_secs((days * 86_400 + 5 * 3_600) as u64)
+}
+
+pub struct Client {
+    config: Config,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(TIMEOUT)
+            .user_agent(USER_AGENT)
+            .build();
+        Self { config, agent }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    fn session_cookie(&self) -> Result<String, String> {
+        match &self.config.session {
+            Some(session) => Ok(format!("session={}", session)),
+            None => Err(format!(
+                "No session token: set {} or add a session line to {}",
+                SESSION_VAR, CONFIG_FILE
+            )),
+        }
+    }
+
+    /// Downloads the puzzle input of `day`.
+    pub fn fetch_input(&self, day: i32) -> Result<String, String> {
+        let url = self.url(&format!("/{}/day/{}/input", YEAR, day));
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &self.session_cookie()?)
+            .call();
+        body(day, &url, response)
+    }
+
+    /// Submits `answer` for a part of `day` and returns the page sent back.
+    pub fn post_answer(&self, day: i32, part: Part, answer: &str) -> Result<String, String> {
+        let url = self.url(&format!("/{}/day/{}/answer", YEAR, day));
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &self.session_cookie()?)
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+        body(day, &url, response)
+    }
+}
+
+fn body(
+    day: i32,
+    url: &str,
+    response: Result<ureq::Response, ureq::Error>,
+) -> Result<String, String> {
+    match response {
+        Ok(response) => response
+            .into_string()
+            .map_err(|e| format!("Unable to read the response from {}: {}", url, e)),
+        Err(ureq::Error::Status(404, _)) => Err(format!("Day {} is not available", day)),
+        Err(ureq::Error::Status(code, _)) => Err(format!(
+            "{} answered with status {}; is the session token still valid?",
+            url, code
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Returns the input file of `day` under `dir`, downloading it first if it is missing or
+/// empty, and whether it was downloaded. A cached input is never downloaded again, and a
+/// day that has not unlocked at `now` is refused.
+pub fn fetch(
+    client: &Client,
+    dir: &Path,
+    day: i32,
+    now: SystemTime,
+) -> Result<(PathBuf, bool), String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("Invalid day: {}", day));
+    }
+    let path = dir.join(format!("day{:02}", day));
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+        return Ok((path, false));
+    }
+    if now < unlock_time(day) {
+        let wait = unlock_time(day).duration_since(now).unwrap_or_default();
+        return Err(format!(
+            "Day {} unlocks in {}h{:02}m",
+            day,
+            wait.as_secs() / 3_600,
+            wait.as_secs() / 60 % 60
+        ));
+    }
+
+    let input = client.fetch_input(day)?;
+    let write_error = |e| format!("Unable to write {}: {}", path.display(), e);
+    fs::create_dir_all(dir).map_err(write_error)?;
+    fs::write(&path, input).map_err(write_error)?;
+    Ok((path, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// Serves one canned `(status, body)` response per connection on a local port and
+    /// reports each request: its request line, headers and body. Returns the base URL and
+    /// the requests.
+    fn stub_server(responses: Vec<(u16, String)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                let mut content = vec![0; content_length];
+                std::io::Read::read_exact(&mut reader, &mut content).unwrap();
+                request.push_str(&String::from_utf8(content).unwrap());
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                let _ = tx.send(request);
+            }
+        });
+        (base_url, rx)
+    }
+
+    fn client(base_url: &str) -> Client {
+        Client::new(Config {
+            session: Some("secret".to_string()),
+            base_url: base_url.to_string(),
+        })
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2020_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse("session: abc\nbase_url: http://localhost:8080/\n").unwrap();
+        assert_eq!(config.session.as_deref(), Some("abc"));
+        assert_eq!(config.base_url, "http://localhost:8080/");
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("token abc").is_err());
+        assert!(Config::parse("token: abc").is_err());
+
+        let config = config.with_env(|name| match name {
+            SESSION_VAR => Some("from env".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.session.as_deref(), Some("from env"));
+        assert_eq!(config.base_url, "http://localhost:8080/");
+    }
+
+    #[test]
+    fn test_unlock_time() {
+        let secs = |day| {
+            unlock_time(day)
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(secs(1), 1_606_798_800);
+        assert_eq!(secs(25), 1_608_872_400);
+    }
+
+    #[test]
+    fn test_fetch_caches() {
+        let (base_url, requests) = stub_server(vec![(200, "1721\n979\n".to_string())]);
+        let client = client(&base_url);
+        let dir = temp_dir("fetch");
+        let now = unlock_time(1) + Duration::from_secs(1);
+
+        let first = fetch(&client, &dir, 1, now);
+        let request = requests.recv().unwrap();
+        let second = fetch(&client, &dir, 1, now);
+        let content = fs::read_to_string(dir.join("day01"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, Ok((dir.join("day01"), true)));
+        assert_eq!(second, Ok((dir.join("day01"), false)));
+        assert_eq!(content.unwrap(), "1721\n979\n");
+        assert!(request.starts_with("GET /2020/day/1/input "));
+        assert!(request.contains("session=secret"));
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_post_answer() {
+        let (base_url, requests) =
+            stub_server(vec![(200, "<p>That's the right answer!</p>".to_string())]);
+        let page = client(&base_url).post_answer(1, Part::Two, "241861950");
+        let request = requests.recv().unwrap();
+
+        assert_eq!(page.unwrap(), "<p>That's the right answer!</p>");
+        assert!(request.starts_with("POST /2020/day/1/answer "));
+        assert!(request.contains("session=secret"));
+        assert!(request.ends_with("level=2&answer=241861950"));
+    }
+
+    #[test]
+    fn test_fetch_refuses() {
+        let (base_url, requests) = stub_server(vec![(404, String::new())]);
+        let client = client(&base_url);
+        let dir = temp_dir("fetch_refuses");
+
+        let locked = fetch(&client, &dir, 2, unlock_time(2) - Duration::from_secs(90));
+        assert_eq!(locked, Err("Day 2 unlocks in 0h01m".to_string()));
+        assert!(fetch(&client, &dir, 26, SystemTime::now()).is_err());
+        assert_eq!(
+            fetch(&client, &dir, 2, unlock_time(2)),
+            Err("Day 2 is not available".to_string())
+        );
+        assert!(requests.recv().is_ok());
+        assert!(!dir.join("day02").exists());
+
+        let mut no_session = Client::new(Config::default());
+        no_session.config.base_url = base_url;
+        assert!(no_session.fetch_input(2).unwrap_err().contains(SESSION_VAR));
+    }
+}
@@ -1,47 +1,161 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
 use std::collections::HashSet;
-use std::io::{self, BufRead, BufReader};
+use std::io;
+
+day_params! {
+    /// The sum the entries must add up to.
+    target: i32 = 2020,
+}
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<i32>;
-    type Output1 = i32;
-    type Output2 = i32;
+    type Params = Params;
+    type Output1 = Option<i64>;
+    type Output2 = Option<i128>;
 
     fn get_day(&self) -> i32 {
         1
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines().flatten().flat_map(|s| s.parse::<i32>()).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, parse_num)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut all_numbers = HashSet::<i32>::new();
-        for i in input {
-            if all_numbers.contains(&(2020 - i)) {
-                return i * (2020 - i);
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1 {
+        let target = i64::from(params.target);
+        let mut all_numbers = HashSet::<i64>::new();
+        for &i in input {
+            let i = i64::from(i);
+            if all_numbers.contains(&(target - i)) {
+                trace!("pair", first = target - i, second = i);
+                return Some(i * (target - i));
             }
-            all_numbers.insert(*i);
+            all_numbers.insert(i);
         }
-        0
+        None
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        for (fst_index, i) in input.iter().enumerate() {
-            let mut all_numbers = HashSet::<i32>::new();
-            for (snd_index, j) in input.iter().enumerate() {
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
+        let target = i64::from(params.target);
+        for (fst_index, &i) in input.iter().enumerate() {
+            let i = i64::from(i);
+            let mut all_numbers = HashSet::<i64>::new();
+            for (snd_index, &j) in input.iter().enumerate() {
+                let j = i64::from(j);
                 if snd_index == fst_index {
                     continue;
                 }
-                if all_numbers.contains(&(2020 - i - j)) {
-                    return i * j * (2020 - i - j);
+                if all_numbers.contains(&(target - i - j)) {
+                    trace!("triple", first = i, second = target - i - j, third = j);
+                    return Some(i128::from(i * j) * i128::from(target - i - j));
                 }
-                all_numbers.insert(*j);
+                all_numbers.insert(j);
             }
         }
-        0
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Rng;
+
+    /// Products of every pair of entries that sum to 2020.
+    fn brute_force_pairs(numbers: &[i32]) -> Vec<i32> {
+        let mut products = Vec::new();
+        for i in 0..numbers.len() {
+            for j in i + 1..numbers.len() {
+                if numbers[i] + numbers[j] == 2020 {
+                    products.push(numbers[i] * numbers[j]);
+                }
+            }
+        }
+        products
+    }
+
+    /// Products of every triple of entries that sum to 2020.
+    fn brute_force_triples(numbers: &[i32]) -> Vec<i32> {
+        let mut products = Vec::new();
+        for i in 0..numbers.len() {
+            for j in i + 1..numbers.len() {
+                for k in j + 1..numbers.len() {
+                    if numbers[i] + numbers[j] + numbers[k] == 2020 {
+                        products.push(numbers[i] * numbers[j] * numbers[k]);
+                    }
+                }
+            }
+        }
+        products
+    }
+
+    /// Distinct entries with exactly one planted pair and one planted triple summing to
+    /// 2020, and their products.
+    fn generate(rng: &mut Rng) -> (String, i32, i32) {
+        loop {
+            let a = rng.range(1..2020) as i32;
+            let x = rng.range(1..2018) as i32;
+            let y = rng.range(1..(2019 - x) as i64) as i32;
+            let mut numbers = vec![a, 2020 - a, x, y, 2020 - x - y];
+            let len = rng.range(5..16) as usize;
+            while numbers.len() < len {
+                numbers.push(rng.range(1..2020) as i32);
+            }
+
+            let mut distinct = numbers.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            if distinct.len() == numbers.len()
+                && brute_force_pairs(&numbers).len() == 1
+                && brute_force_triples(&numbers).len() == 1
+            {
+                rng.shuffle(&mut numbers);
+                let lines: Vec<_> = numbers.iter().map(i32::to_string).collect();
+                return (lines.join("\n"), a * (2020 - a), x * y * (2020 - x - y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let problem = Problem {};
+        for seed in 0..200 {
+            let (raw_input, pair, triple) = generate(&mut Rng::new(seed));
+            let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+            assert_eq!(brute_force_pairs(&input), vec![pair], "seed {}", seed);
+            assert_eq!(brute_force_triples(&input), vec![triple], "seed {}", seed);
+            assert_eq!(
+                problem.solve_first(&input, &Params::default()),
+                Some(i64::from(pair)),
+                "seed {}",
+                seed
+            );
+            assert_eq!(
+                problem.solve_second(&input, &Params::default()),
+                Some(i128::from(triple)),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_extremes() {
+        let problem = Problem {};
+        let raw_input = "-2147483648\n2147483647\n2147483647\n-2147483648\n";
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        let params = Params { target: -1 };
+        assert_eq!(
+            problem.solve_first(&input, &params),
+            Some(-(1 << 62) + (1 << 31))
+        );
+        assert_eq!(problem.solve_second(&input, &params), None);
+        assert_eq!(problem.solve_first(&input, &Params::default()), None);
+        let params = Params { target: i32::MIN };
+        assert_eq!(problem.solve_first(&input, &params), None);
+        assert_eq!(problem.solve_second(&input, &params), None);
     }
 }
@@ -1,25 +1,32 @@
-use crate::solver::Solver;
-use regex::Regex;
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use regex::{Captures, Regex};
+use std::io;
 
 pub struct PasswordEntry {
-    letter: char,
-    low_pos: usize,
-    high_pos: usize,
-    password: String,
+    pub letter: char,
+    pub low_pos: usize,
+    pub high_pos: usize,
+    pub password: String,
 }
 
 impl PasswordEntry {
-    fn from_string(passwd: &str) -> Self {
+    fn from_string(passwd: &str) -> Result<Self, ParseError> {
         let re = Regex::new(r"(?P<low>\d+)-(?P<high>\d+) (?P<letter>[a-z]): (?P<password>[a-z]+)")
             .unwrap();
-        let caps = re.captures(passwd).unwrap();
-        Self {
+        let caps = re
+            .captures(passwd)
+            .ok_or_else(|| ParseError::new("expected \"<low>-<high> <letter>: <password>\""))?;
+        let num = |caps: &Captures, name| {
+            let m = caps.name(name).unwrap();
+            parse_num(m.as_str()).map_err(|e: ParseError| e.at_column(m.start() + 1))
+        };
+        Ok(Self {
             letter: caps["letter"].chars().next().unwrap(),
-            low_pos: caps["low"].parse().unwrap(),
-            high_pos: caps["high"].parse().unwrap(),
+            low_pos: num(&caps, "low")?,
+            high_pos: num(&caps, "high")?,
             password: caps["password"].to_string(),
-        }
+        })
     }
 
     fn is_valid_pt1(&self) -> bool {
@@ -33,9 +40,13 @@ impl PasswordEntry {
 
     fn is_valid_pt2(&self) -> bool {
         let letters: Vec<_> = self.password.chars().collect();
-        let low_char = letters[self.low_pos - 1];
-        let high_char = letters[self.high_pos - 1];
-        (self.letter == low_char) ^ (self.letter == high_char)
+        // Positions are 1-based; one outside the password never holds the letter.
+        let has_letter = |pos: usize| {
+            pos.checked_sub(1)
+                .and_then(|ind| letters.get(ind))
+                .is_some_and(|&ch| ch == self.letter)
+        };
+        has_letter(self.low_pos) ^ has_letter(self.high_pos)
     }
 }
 
@@ -43,6 +54,7 @@ pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<PasswordEntry>;
+    type Params = ();
     type Output1 = usize;
     type Output2 = usize;
 
@@ -50,19 +62,15 @@ impl Solver for Problem {
         2
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines()
-            .flatten()
-            .map(|s| PasswordEntry::from_string(&s))
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, PasswordEntry::from_string)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         input.iter().filter(|entry| entry.is_valid_pt1()).count()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         input.iter().filter(|entry| entry.is_valid_pt2()).count()
     }
 }
@@ -77,8 +85,12 @@ mod tests {
 1-3 b: cdefg
 2-9 c: ccccccccc
 ";
-        let entries = input.lines().map(PasswordEntry::from_string).collect();
-        assert_eq!(Problem {}.solve_first(&entries), 2);
+        let entries = input
+            .lines()
+            .map(PasswordEntry::from_string)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(Problem {}.solve_first(&entries, &()), 2);
     }
 
     #[test]
@@ -87,7 +99,21 @@ mod tests {
 1-3 b: cdefg
 2-9 c: ccccccccc
 ";
-        let entries = input.lines().map(PasswordEntry::from_string).collect();
-        assert_eq!(Problem {}.solve_second(&entries), 1);
+        let entries = input
+            .lines()
+            .map(PasswordEntry::from_string)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(Problem {}.solve_second(&entries, &()), 1);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "1-3 a: abcde
+1-3 b cdefg
+";
+        let e = Problem {}.parse_input(input.as_bytes()).err().unwrap();
+        assert_eq!(e.line(), Some(2));
+        assert_eq!(e.text(), Some("1-3 b cdefg"));
     }
 }
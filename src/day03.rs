@@ -1,44 +1,109 @@
+use crate::error::ParseError;
+use crate::grid::{Cells, Grid};
 use crate::solver::Solver;
-use std::collections::HashSet;
-use std::io::{self, BufRead, BufReader};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Square {
+    Open,
+    Tree,
+}
 
-type Position = (usize, usize);
+impl Square {
+    fn from_char(ch: char) -> Result<Self, ParseError> {
+        match ch {
+            '.' => Ok(Self::Open),
+            '#' => Ok(Self::Tree),
+            _ => Err(ParseError::new(format!("unrecognizable char {:?}", ch))),
+        }
+    }
+}
 
-pub struct Grid {
-    height: usize,
-    width: usize,
-    tree_list: HashSet<Position>,
+/// Squares moved right and down per step, written `right/down`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slope {
+    pub right: usize,
+    pub down: usize,
 }
 
-impl Grid {
-    fn step(&self, cur_pos: Position, step: Position) -> Position {
-        ((cur_pos.0 + step.0) % self.width, cur_pos.1 + step.1)
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid slope {:?}", s);
+        let (right, down) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let (right, down) = (
+            right.parse().map_err(|_| invalid())?,
+            down.parse().map_err(|_| invalid())?,
+        );
+        // A slope that never goes down would never leave the map.
+        if down == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { right, down })
     }
+}
 
-    fn is_tree(&self, pos: Position) -> bool {
-        self.tree_list.contains(&pos)
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.right, self.down)
     }
 }
 
+/// A comma-separated list of slopes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Slopes(pub Vec<Slope>);
+
+impl FromStr for Slopes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for Slopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slopes: Vec<_> = self.0.iter().map(Slope::to_string).collect();
+        f.write_str(&slopes.join(","))
+    }
+}
+
+day_params! {
+    /// The slope of part 1.
+    slope: Slope = Slope { right: 3, down: 1 },
+    /// The slopes whose tree counts part 2 multiplies.
+    slopes: Slopes = Slopes(
+        [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&(right, down)| Slope { right, down })
+            .collect()
+    ),
+}
+
 pub struct Problem;
 
 impl Problem {
-    fn traverse_slope(&self, input: &Grid, step: Position) -> i64 {
-        let mut pos = (0, 0);
-        let mut result = 0;
-        while pos.1 < input.height {
-            pos = input.step(pos, step);
-            if input.is_tree(pos) {
-                result += 1;
-            }
-        }
-
-        result
+    /// Counts the trees met going down `input`, which repeats to the right, along `slope`.
+    fn traverse_slope(&self, input: &Grid<Square>, slope: Slope) -> i64 {
+        let height = input.height() as i64;
+        input
+            .toroidal()
+            .ray((0, 0), (slope.right as i64, slope.down as i64))
+            .take_while(|&((_, y), _)| y < height)
+            .filter(|&(_, &square)| square == Square::Tree)
+            .count() as i64
     }
 }
 
 impl Solver for Problem {
-    type Input = Grid;
+    type Input = Grid<Square>;
+    type Params = Params;
     type Output1 = i64;
     type Output2 = i64;
 
@@ -46,40 +111,20 @@ impl Solver for Problem {
         3
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let mut tree_list = HashSet::new();
-        let (mut height, mut width) = (0, 0);
-        let r = BufReader::new(r);
-
-        for (row, line) in r.lines().flatten().enumerate() {
-            if width == 0 {
-                width = line.len();
-            }
-            height += 1;
-            for (col, ch) in line.chars().enumerate() {
-                if ch == '#' {
-                    tree_list.insert((col, row));
-                }
-            }
-        }
-
-        Grid {
-            width,
-            height,
-            tree_list,
-        }
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        Grid::parse(r, Square::from_char)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let step = (3, 1);
-        self.traverse_slope(input, step)
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1 {
+        self.traverse_slope(input, params.slope)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let steps = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
-        steps
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
+        params
+            .slopes
+            .0
             .iter()
-            .map(|step| self.traverse_slope(input, *step))
+            .map(|slope| self.traverse_slope(input, *slope))
             .product()
     }
 }
@@ -102,8 +147,8 @@ mod tests {
 #...##....##...##....##...##....##...##....##...##....##...##....#
 .#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.# ";
         let problem = Problem {};
-        let grid = problem.parse_input(input.as_bytes());
-        assert_eq!(problem.solve_first(&grid), 7);
+        let grid = problem.parse_input(input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&grid, &Params::default()), 7);
     }
 
     #[test]
@@ -120,7 +165,21 @@ mod tests {
 #...##....##...##....##...##....##...##....##...##....##...##....#
 .#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.#.#..#...#.# ";
         let problem = Problem {};
-        let grid = problem.parse_input(input.as_bytes());
-        assert_eq!(problem.solve_second(&grid), 336);
+        let grid = problem.parse_input(input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&grid, &Params::default()), 336);
+        let params = Params {
+            slopes: "1/1,1/2".parse().unwrap(),
+            ..Params::default()
+        };
+        assert_eq!(problem.solve_second(&grid, &params), 4);
+    }
+
+    #[test]
+    fn test_slopes() {
+        let slopes = Params::default().slopes;
+        assert_eq!(slopes.to_string(), "1/1,3/1,5/1,7/1,1/2");
+        assert_eq!(slopes.to_string().parse(), Ok(slopes));
+        assert!("3/0".parse::<Slope>().is_err());
+        assert!("3,1".parse::<Slopes>().is_err());
     }
 }
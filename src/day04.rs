@@ -1,18 +1,18 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{read_string, Solver};
 use regex::Regex;
 use std::io;
 
 #[derive(Default)]
 pub struct Passport {
-    byr: Option<String>,
-    iyr: Option<String>,
-    eyr: Option<String>,
-    hgt: Option<String>,
-    hcl: Option<String>,
-    ecl: Option<String>,
-    pid: Option<String>,
-    #[allow(dead_code)]
-    cid: Option<String>,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    pub hgt: Option<String>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
 }
 
 impl Passport {
@@ -79,7 +79,7 @@ impl Passport {
         match &self.byr {
             Some(s) => s
                 .parse::<i32>()
-                .map_or(false, |num| num >= 1920 && num <= 2002),
+                .is_ok_and(|num| (1920..=2002).contains(&num)),
             None => false,
         }
     }
@@ -88,7 +88,7 @@ impl Passport {
         match &self.iyr {
             Some(s) => s
                 .parse::<i32>()
-                .map_or(false, |num| num >= 2010 && num <= 2020),
+                .is_ok_and(|num| (2010..=2020).contains(&num)),
             None => false,
         }
     }
@@ -97,28 +97,29 @@ impl Passport {
         match &self.eyr {
             Some(s) => s
                 .parse::<i32>()
-                .map_or(false, |num| num >= 2020 && num <= 2030),
+                .is_ok_and(|num| (2020..=2030).contains(&num)),
             None => false,
         }
     }
 
     fn is_hgt_valid(&self) -> bool {
         let re = Regex::new(r"^(?P<num>\d+)(?P<unit>cm|in)$").unwrap();
-        self.hgt.as_deref().map_or(false, |s| {
-            re.captures(s).map_or(false, |caps| {
-                let num: i32 = caps["num"].parse().unwrap();
-                match &caps["unit"] {
-                    "cm" => num >= 150 && num <= 193,
-                    "in" => num >= 59 && num <= 76,
-                    _ => false,
-                }
+        self.hgt.as_deref().is_some_and(|s| {
+            re.captures(s).is_some_and(|caps| {
+                caps["num"]
+                    .parse::<i32>()
+                    .is_ok_and(|num| match &caps["unit"] {
+                        "cm" => (150..=193).contains(&num),
+                        "in" => (59..=76).contains(&num),
+                        _ => false,
+                    })
             })
         })
     }
 
     fn is_hcl_valid(&self) -> bool {
         let re = Regex::new(r"^#[a-f0-9]{6}$").unwrap();
-        self.hcl.as_deref().map_or(false, |s| re.is_match(s))
+        self.hcl.as_deref().is_some_and(|s| re.is_match(s))
     }
 
     fn is_ecl_valid(&self) -> bool {
@@ -130,7 +131,7 @@ impl Passport {
 
     fn is_pid_valid(&self) -> bool {
         let re = Regex::new(r"^\d{9}$").unwrap();
-        self.pid.as_deref().map_or(false, |s| re.is_match(s))
+        self.pid.as_deref().is_some_and(|s| re.is_match(s))
     }
 }
 
@@ -138,6 +139,7 @@ pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<Passport>;
+    type Params = ();
     type Output1 = usize;
     type Output2 = usize;
 
@@ -145,19 +147,17 @@ impl Solver for Problem {
         4
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let mut buf = String::new();
-        let mut r = r;
-        let _ = r.read_to_string(&mut buf);
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buf = read_string(r)?;
         let lines = buf.split("\n\n");
-        lines.map(Passport::from_string).collect()
+        Ok(lines.map(Passport::from_string).collect())
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         input.iter().filter(|entry| entry.is_valid_pt1()).count()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         input.iter().filter(|entry| entry.is_valid_pt2()).count()
     }
 }
@@ -183,8 +183,8 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in
  ";
         let problem = Problem {};
-        let grid = problem.parse_input(input.as_bytes());
-        assert_eq!(problem.solve_first(&grid), 2);
+        let grid = problem.parse_input(input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&grid, &()), 2);
     }
 
     #[test]
@@ -204,8 +204,8 @@ eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007
 ";
         let problem = Problem {};
-        let grid = problem.parse_input(input.as_bytes());
-        assert_eq!(problem.solve_second(&grid), 0);
+        let grid = problem.parse_input(input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&grid, &()), 0);
     }
 
     #[test]
@@ -224,7 +224,7 @@ eyr:2022
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
 ";
         let problem = Problem {};
-        let grid = problem.parse_input(input.as_bytes());
-        assert_eq!(problem.solve_second(&grid), 4);
+        let grid = problem.parse_input(input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&grid, &()), 4);
     }
 }
@@ -1,17 +1,29 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::solver::{parse_lines, Solver};
+use std::io;
 
 pub struct Seat {
-    row: i32,
-    col: i32,
+    pub row: i32,
+    pub col: i32,
 }
 
 impl Seat {
-    fn from_string(s: &str) -> Self {
-        Self {
+    fn from_string(s: &str) -> Result<Self, ParseError> {
+        if s.len() != 10 {
+            return Err(ParseError::new("expected 10 characters"));
+        }
+        for (ind, ch) in s.chars().enumerate() {
+            let expected = if ind < 7 { ['F', 'B'] } else { ['L', 'R'] };
+            if !expected.contains(&ch) {
+                let message = format!("expected {:?} or {:?}", expected[0], expected[1]);
+                return Err(ParseError::new(message).at_column(ind + 1));
+            }
+        }
+
+        Ok(Self {
             row: Self::binary_seek(&s[..7], 'F', 128),
             col: Self::binary_seek(&s[7..], 'L', 8),
-        }
+        })
     }
 
     fn binary_seek(s: &str, lower_ch: char, upper_lim: i32) -> i32 {
@@ -34,23 +46,23 @@ pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<Seat>;
-    type Output1 = i32;
-    type Output2 = i32;
+    type Params = ();
+    type Output1 = Option<i32>;
+    type Output2 = Option<i32>;
 
     fn get_day(&self) -> i32 {
         5
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines().flatten().map(|s| Seat::from_string(&s)).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, Seat::from_string)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        input.iter().map(|seat| seat.seat_id()).max().unwrap()
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
+        input.iter().map(|seat| seat.seat_id()).max()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         const SEATS: usize = 128 * 8;
         let mut seat_exists = [false; SEATS];
         for seat_id in input.iter().map(|seat| seat.seat_id()) {
@@ -65,19 +77,92 @@ impl Solver for Problem {
                     && seat_exists[ind - 1]
                     && seat_exists[ind + 1]
             })
-            .unwrap() as i32
+            .map(|ind| ind as i32)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::random::Rng;
+
+    fn boarding_pass(id: i32) -> String {
+        (0..10)
+            .rev()
+            .map(|bit| match (bit >= 3, id >> bit & 1 == 1) {
+                (true, false) => 'F',
+                (true, true) => 'B',
+                (false, false) => 'L',
+                (false, true) => 'R',
+            })
+            .collect()
+    }
+
+    /// Returns the highest seat id and the one missing between the lowest and highest.
+    fn brute_force(passes: &[&str]) -> (i32, i32) {
+        let ids: Vec<i32> = passes
+            .iter()
+            .map(|pass| {
+                pass.chars()
+                    .fold(0, |id, ch| id * 2 + (ch == 'B' || ch == 'R') as i32)
+            })
+            .collect();
+        let (min, max) = (*ids.iter().min().unwrap(), *ids.iter().max().unwrap());
+        let missing = (min..max).find(|id| !ids.contains(id)).unwrap();
+        (max, missing)
+    }
+
+    /// Boarding passes for a run of seats with one missing, the highest seat id and the
+    /// missing one.
+    fn generate(rng: &mut Rng) -> (String, i32, i32) {
+        let low = rng.range(8..1000) as i32;
+        let high = rng.range(low as i64 + 2..1016) as i32;
+        let missing = rng.range(low as i64 + 1..high as i64) as i32;
+        let mut passes: Vec<_> = (low..=high)
+            .filter(|&id| id != missing)
+            .map(boarding_pass)
+            .collect();
+        rng.shuffle(&mut passes);
+        (passes.join("\n"), high, missing)
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let problem = Problem {};
+        for seed in 0..200 {
+            let (raw_input, high, missing) = generate(&mut Rng::new(seed));
+            let passes: Vec<_> = raw_input.lines().collect();
+            assert_eq!(brute_force(&passes), (high, missing), "seed {}", seed);
+            let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+            assert_eq!(
+                problem.solve_first(&input, &()),
+                Some(high),
+                "seed {}",
+                seed
+            );
+            assert_eq!(
+                problem.solve_second(&input, &()),
+                Some(missing),
+                "seed {}",
+                seed
+            );
+        }
+    }
 
     #[test]
     fn test_seat_id() {
-        assert_eq!(Seat::from_string("FBFBBFFRLR").seat_id(), 357);
-        assert_eq!(Seat::from_string("BFFFBBFRRR").seat_id(), 567);
-        assert_eq!(Seat::from_string("FFFBBBFRRR").seat_id(), 119);
-        assert_eq!(Seat::from_string("BBFFBBFRLL").seat_id(), 820);
+        assert_eq!(Seat::from_string("FBFBBFFRLR").unwrap().seat_id(), 357);
+        assert_eq!(Seat::from_string("BFFFBBFRRR").unwrap().seat_id(), 567);
+        assert_eq!(Seat::from_string("FFFBBBFRRR").unwrap().seat_id(), 119);
+        assert_eq!(Seat::from_string("BBFFBBFRLL").unwrap().seat_id(), 820);
+    }
+
+    #[test]
+    fn test_invalid_seat() {
+        assert_eq!(Seat::from_string("FBFBBFFRL").err().unwrap().column(), None);
+        assert_eq!(
+            Seat::from_string("FBFBBLFRLR").err().unwrap().column(),
+            Some(6)
+        );
     }
 }
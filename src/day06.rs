@@ -1,14 +1,16 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{read_string, Solver};
 use std::collections::HashSet;
 use std::io;
 
-type Person = String;
-type Group = Vec<Person>;
+pub type Person = String;
+pub type Group = Vec<Person>;
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<Group>;
+    type Params = ();
     type Output1 = usize;
     type Output2 = usize;
 
@@ -16,16 +18,15 @@ impl Solver for Problem {
         6
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let mut buf = String::new();
-        let mut r = r;
-        let _ = r.read_to_string(&mut buf);
-        buf.split("\n\n")
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buf = read_string(r)?;
+        Ok(buf
+            .split("\n\n")
             .map(|group| group.lines().map(|s| s.to_string()).collect())
-            .collect()
+            .collect())
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         let question_per_group = |group: &Group| {
             group
                 .iter()
@@ -39,15 +40,17 @@ impl Solver for Problem {
         input.iter().map(question_per_group).sum()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         let question_per_group = |group: &Group| {
-            group
-                .iter()
-                .fold(group[0].chars().collect::<HashSet<_>>(), |set, s| {
-                    let string_chars: HashSet<_> = s.chars().collect();
-                    set.intersection(&string_chars).cloned().collect()
-                })
-                .len()
+            let mut people = group.iter().map(|s| s.chars().collect::<HashSet<_>>());
+            match people.next() {
+                Some(first) => people
+                    .fold(first, |set, string_chars| {
+                        set.intersection(&string_chars).cloned().collect()
+                    })
+                    .len(),
+                None => 0,
+            }
         };
 
         input.iter().map(question_per_group).sum()
@@ -77,8 +80,8 @@ a
 b
 ";
         let prob = Problem {};
-        let input = prob.parse_input(raw_input.as_bytes());
-        assert_eq!(prob.solve_first(&input), 11);
+        let input = prob.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(prob.solve_first(&input, &()), 11);
     }
 
     #[test]
@@ -100,7 +103,7 @@ a
 b
 ";
         let prob = Problem {};
-        let input = prob.parse_input(raw_input.as_bytes());
-        assert_eq!(prob.solve_second(&input), 6);
+        let input = prob.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(prob.solve_second(&input, &()), 6);
     }
 }
@@ -1,93 +1,148 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
 use regex::Regex;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
-type Bag = String;
-type BagContent = Vec<(String, i32)>;
+pub type Bag = String;
+pub type BagContent = Vec<(String, i32)>;
 #[derive(Debug, Default)]
 pub struct BagEntry {
-    content: BagContent,
-    parents: Vec<Bag>,
+    pub content: BagContent,
+    pub parents: Vec<Bag>,
+}
+pub type BagRule = HashMap<Bag, BagEntry>;
+
+/// Returns a bag that contains itself, directly or through other bags.
+fn find_cycle(rules: &BagRule) -> Option<&str> {
+    fn visit<'a>(
+        rules: &'a BagRule,
+        bag: &'a str,
+        done: &mut HashSet<&'a str>,
+        path: &mut HashSet<&'a str>,
+    ) -> Option<&'a str> {
+        if done.contains(bag) {
+            return None;
+        }
+        if !path.insert(bag) {
+            return Some(bag);
+        }
+        for (child_bag, _) in rules[bag].content.iter() {
+            if let Some(bag) = visit(rules, child_bag, done, path) {
+                return Some(bag);
+            }
+        }
+        path.remove(bag);
+        done.insert(bag);
+        None
+    }
+
+    let (mut done, mut path) = (HashSet::new(), HashSet::new());
+    rules
+        .keys()
+        .find_map(|bag| visit(rules, bag, &mut done, &mut path))
+}
+
+day_params! {
+    /// The bag whose containers and contents are counted.
+    bag: String = "shiny gold".to_string(),
 }
-type BagRule = HashMap<Bag, BagEntry>;
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = BagRule;
+    type Params = Params;
     type Output1 = usize;
-    type Output2 = i32;
+    type Output2 = Option<i32>;
 
     fn get_day(&self) -> i32 {
         7
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
         let mut result: Self::Input = HashMap::new();
 
         let bag_name_regex = Regex::new(r"^(?P<bag_name>\w+ \w+) bag").unwrap();
         let content_list_regex = Regex::new(r"(?P<count>\d+) (?P<child_bag>\w+ \w+) bag").unwrap();
-        for rule in r.lines().flatten().filter(|l| !l.is_empty()) {
-            let bag_name = bag_name_regex.captures(&rule).unwrap()["bag_name"].to_string();
-            if rule.contains("no other") {
-                result.entry(bag_name).or_default();
-            } else {
-                let mut content: BagContent = Vec::new();
-                for captures in content_list_regex.captures_iter(&rule) {
-                    let count: i32 = captures["count"].parse().unwrap();
-                    let child_bag = captures["child_bag"].to_string();
-                    result
-                        .entry(child_bag.clone())
-                        .or_default()
-                        .parents
-                        .push(bag_name.clone());
-                    content.push((child_bag, count));
+        let rules = parse_lines(r, |rule| {
+            let bag_name = bag_name_regex
+                .captures(rule)
+                .ok_or_else(|| ParseError::new("expected \"<adjective> <color> bags contain\""))?
+                ["bag_name"]
+                .to_string();
+            let mut content: BagContent = Vec::new();
+            if !rule.contains("no other") {
+                for captures in content_list_regex.captures_iter(rule) {
+                    let count = captures.name("count").unwrap();
+                    let count: i32 =
+                        parse_num(count.as_str()).map_err(|e| e.at_column(count.start() + 1))?;
+                    content.push((captures["child_bag"].to_string(), count));
                 }
+            }
+            Ok((bag_name, content))
+        })?;
 
-                result.entry(bag_name).or_default().content = content;
+        for (bag_name, content) in rules {
+            for (child_bag, _) in content.iter() {
+                result
+                    .entry(child_bag.clone())
+                    .or_default()
+                    .parents
+                    .push(bag_name.clone());
             }
+            result.entry(bag_name).or_default().content = content;
         }
 
-        result
+        match find_cycle(&result) {
+            Some(bag) => Err(ParseError::new(format!("{} bags contain themselves", bag))),
+            None => Ok(result),
+        }
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let orig_bag = "shiny gold";
-        let mut processing_bags: VecDeque<_> = input[orig_bag].parents.iter().collect();
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1 {
+        let mut processing_bags: VecDeque<_> = match input.get(&params.bag) {
+            Some(entry) => entry.parents.iter().collect(),
+            None => return 0,
+        };
         let mut parent_bags = HashSet::new();
 
         while let Some(next_bag) = processing_bags.pop_front() {
-            parent_bags.insert(next_bag.clone());
-            processing_bags.extend(input[next_bag].parents.iter());
+            if parent_bags.insert(next_bag) {
+                processing_bags.extend(input[next_bag].parents.iter());
+            }
         }
 
         parent_bags.len()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut bag_count: HashMap<String, i32> = HashMap::new();
-
-        fn bag_count_fn(input: &BagRule, bag: &str, bag_count: &mut HashMap<String, i32>) -> i32 {
-            if bag_count.contains_key(bag) {
-                bag_count[bag]
-            } else if input[bag].content.is_empty() {
-                bag_count.insert(bag.to_string(), 0);
-                0
-            } else {
-                let result: i32 = input[bag]
-                    .content
-                    .iter()
-                    .fold(0, |acc, (child_bag, count)| {
-                        acc + count + count * bag_count_fn(input, child_bag, bag_count)
-                    });
-                bag_count.insert(bag.to_string(), result);
-                result
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
+        let mut bag_count: HashMap<String, Option<i32>> = HashMap::new();
+
+        // None when the count does not fit in an i32.
+        fn bag_count_fn(
+            input: &BagRule,
+            bag: &str,
+            bag_count: &mut HashMap<String, Option<i32>>,
+        ) -> Option<i32> {
+            if let Some(&count) = bag_count.get(bag) {
+                return count;
             }
+            let result = input[bag]
+                .content
+                .iter()
+                .try_fold(0i32, |acc, (child_bag, count)| {
+                    let inner = bag_count_fn(input, child_bag, bag_count)?;
+                    acc.checked_add(count.checked_mul(inner.checked_add(1)?)?)
+                });
+            bag_count.insert(bag.to_string(), result);
+            result
         }
 
-        bag_count_fn(input, "shiny gold", &mut bag_count)
+        if !input.contains_key(&params.bag) {
+            return Some(0);
+        }
+        bag_count_fn(input, &params.bag, &mut bag_count)
     }
 }
 
@@ -108,8 +163,8 @@ faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
 
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 4);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &Params::default()), 4);
     }
 
     #[test]
@@ -125,8 +180,8 @@ faded blue bags contain no other bags.
 dotted black bags contain no other bags.";
 
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 32);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &Params::default()), Some(32));
     }
 
     #[test]
@@ -141,7 +196,12 @@ dark violet bags contain no other bags.
 ";
 
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 126);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &Params::default()), Some(126));
+        let params = Params {
+            bag: "dark green".to_string(),
+        };
+        assert_eq!(problem.solve_first(&input, &params), 4);
+        assert_eq!(problem.solve_second(&input, &params), Some(6));
     }
 }
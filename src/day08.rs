@@ -1,112 +1,230 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
 use std::collections::HashSet;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Inst {
-    NOP(i32),
-    ACC(i32),
-    JMP(i32),
+    Nop(i32),
+    Acc(i32),
+    Jmp(i32),
 }
 
 impl Inst {
-    fn from_string(inst: &str) -> Self {
-        let parse_inst: Vec<_> = inst.split(' ').collect();
-        let num: i32 = parse_inst[1].parse().unwrap();
-        match parse_inst[0] {
-            "nop" => Self::NOP(num),
-            "acc" => Self::ACC(num),
-            "jmp" => Self::JMP(num),
-            _ => panic!("Invalid instruction!"),
+    fn from_string(inst: &str) -> Result<Self, ParseError> {
+        let (op, arg) = inst
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new("expected \"<operation> <argument>\""))?;
+        let num: i32 = parse_num(arg).map_err(|e| e.at_column(op.len() + 2))?;
+        match op {
+            "nop" => Ok(Self::Nop(num)),
+            "acc" => Ok(Self::Acc(num)),
+            "jmp" => Ok(Self::Jmp(num)),
+            _ => Err(ParseError::new(format!("invalid instruction {:?}", op)).at_column(1)),
         }
     }
 
-    fn execute(&self, acc: i32, ind: i32) -> (i32, i32) {
-        match self {
-            Self::NOP(_) => (acc, ind + 1),
-            Self::ACC(i) => (acc + i, ind + 1),
-            Self::JMP(i) => (acc, ind + i),
+    fn execute(&self, acc: i64, ind: i64) -> (i64, i64) {
+        match *self {
+            Self::Nop(_) => (acc, ind + 1),
+            Self::Acc(i) => (acc + i64::from(i), ind + 1),
+            Self::Jmp(i) => (acc, ind + i64::from(i)),
         }
     }
 }
 
 struct ProgramState {
     success: bool,
-    #[allow(dead_code)]
-    ind: usize,
-    acc: i32,
+    ind: i64,
+    acc: i64,
     indices: HashSet<usize>,
 }
 
 pub struct Problem;
 
 impl Problem {
+    /// Runs `input` until an instruction is about to run a second time or the program
+    /// jumps outside itself, which ends it.
     fn run_program(&self, input: &[Inst]) -> ProgramState {
         let mut indices = HashSet::new();
         let (mut acc, mut ind) = (0, 0);
+        let outside = |ind: i64| ind < 0 || ind >= input.len() as i64;
 
-        loop {
-            indices.insert(ind);
-            let new_result = input[ind].execute(acc, ind as i32);
+        while !outside(ind) && indices.insert(ind as usize) {
+            let new_result = input[ind as usize].execute(acc, ind);
             acc = new_result.0;
-            ind = new_result.1 as usize;
-
-            if indices.contains(&ind) || ind >= input.len() {
-                return ProgramState {
-                    acc,
-                    ind,
-                    indices,
-                    success: ind >= input.len(),
-                };
-            }
+            ind = new_result.1;
+        }
+        ProgramState {
+            acc,
+            ind,
+            indices,
+            success: outside(ind),
         }
     }
 }
 
 impl Solver for Problem {
     type Input = Vec<Inst>;
-    type Output1 = i32;
-    type Output2 = i32;
+    type Params = ();
+    type Output1 = i64;
+    type Output2 = Option<i64>;
 
     fn get_day(&self) -> i32 {
         8
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines().flatten().map(|s| Inst::from_string(&s)).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, Inst::from_string)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let ProgramState { acc, .. } = self.run_program(input);
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
+        let ProgramState {
+            acc, ind, success, ..
+        } = self.run_program(input);
+        trace!("stopped", line = ind + 1, looped = !success, acc = acc);
         acc
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         let ProgramState { indices, .. } = self.run_program(input);
 
         for index in indices {
             let mut new_input = input.clone();
             let new_inst = match new_input[index] {
-                Inst::NOP(i) => Inst::JMP(i),
-                Inst::JMP(i) => Inst::NOP(i),
+                Inst::Nop(i) => Inst::Jmp(i),
+                Inst::Jmp(i) => Inst::Nop(i),
                 _ => continue,
             };
             new_input[index] = new_inst;
 
             let ProgramState { success, acc, .. } = self.run_program(&new_input);
             if success {
-                return acc;
+                trace!(
+                    "patched",
+                    line = index + 1,
+                    from = format!("{:?}", input[index]),
+                    to = format!("{:?}", new_inst),
+                    acc = acc,
+                );
+                return Some(acc);
             }
         }
 
-        0
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::random::Rng;
+    use crate::trace;
+
+    fn format_inst(inst: &Inst) -> String {
+        match inst {
+            Inst::Nop(i) => format!("nop {:+}", i),
+            Inst::Acc(i) => format!("acc {:+}", i),
+            Inst::Jmp(i) => format!("jmp {:+}", i),
+        }
+    }
+
+    /// Runs `program` until an instruction is about to run a second time or it steps just
+    /// past its end, returning the accumulator, whether it terminated and which
+    /// instructions ran.
+    fn brute_force_run(program: &[Inst]) -> (i64, bool, Vec<bool>) {
+        let mut visited = vec![false; program.len()];
+        let (mut acc, mut ind) = (0, 0);
+        loop {
+            if ind == program.len() as i32 {
+                return (acc, true, visited);
+            }
+            if visited[ind as usize] {
+                return (acc, false, visited);
+            }
+            visited[ind as usize] = true;
+            match program[ind as usize] {
+                Inst::Nop(_) => ind += 1,
+                Inst::Acc(i) => {
+                    acc += i64::from(i);
+                    ind += 1;
+                }
+                Inst::Jmp(i) => ind += i,
+            }
+        }
+    }
+
+    /// Accumulators of every program, one `jmp`/`nop` flip away, that terminates.
+    fn brute_force_fixes(program: &[Inst]) -> Vec<i64> {
+        (0..program.len())
+            .filter_map(|ind| {
+                let mut fixed = program.to_vec();
+                fixed[ind] = match program[ind] {
+                    Inst::Nop(i) => Inst::Jmp(i),
+                    Inst::Jmp(i) => Inst::Nop(i),
+                    Inst::Acc(_) => return None,
+                };
+                match brute_force_run(&fixed) {
+                    (acc, true, _) => Some(acc),
+                    (_, false, _) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// A program with one `nop` on its path corrupted into a backwards `jmp`, so that
+    /// exactly one flip makes it terminate. Returns it with the accumulator at the loop
+    /// and after the fix.
+    ///
+    /// Every jump target, before or after a flip, stays within the program or just past
+    /// its end.
+    fn generate(rng: &mut Rng) -> (String, i64, i64) {
+        loop {
+            let len = rng.range(5..40) as i32;
+            let mut program: Vec<_> = (0..len)
+                .map(|ind| match rng.below(3) {
+                    0 => Inst::Acc(rng.range(-50..51) as i32),
+                    1 => Inst::Jmp(rng.range(1..(len - ind).min(3) as i64 + 1) as i32),
+                    _ => Inst::Nop(rng.range(-ind as i64..(len - ind) as i64 + 1) as i32),
+                })
+                .collect();
+            let (fixed_acc, terminated, ran) = brute_force_run(&program);
+            assert!(terminated, "forward jumps always terminate");
+
+            let candidates: Vec<_> = (0..program.len())
+                .filter(|&ind| ran[ind] && matches!(program[ind], Inst::Nop(i) if i <= 0))
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            let ind = *rng.choose(&candidates);
+            if let Inst::Nop(i) = program[ind] {
+                program[ind] = Inst::Jmp(i);
+            }
+
+            let (loop_acc, terminated, _) = brute_force_run(&program);
+            if !terminated && brute_force_fixes(&program) == vec![fixed_acc] {
+                let lines: Vec<_> = program.iter().map(format_inst).collect();
+                return (lines.join("\n"), loop_acc, fixed_acc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let problem = Problem {};
+        for seed in 0..200 {
+            let (raw_input, loop_acc, fixed_acc) = generate(&mut Rng::new(seed));
+            let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+            assert_eq!(problem.solve_first(&input, &()), loop_acc, "seed {}", seed);
+            assert_eq!(
+                problem.solve_second(&input, &()),
+                Some(fixed_acc),
+                "seed {}",
+                seed
+            );
+        }
+    }
 
     #[test]
     fn test_first() {
@@ -121,8 +239,8 @@ jmp -4
 acc +6
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 5);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 5);
     }
 
     #[test]
@@ -138,7 +256,30 @@ jmp -4
 acc +6
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 8);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), Some(8));
+
+        let (answer, events) =
+            trace::explain(|| trace::capture(|| problem.solve_second(&input, &())));
+        assert_eq!(answer, Some(8));
+        assert_eq!(
+            events.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["patched line=8 from=Jmp(-4) to=Nop(-4) acc=8"]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let raw_input = "nop +0
+acc +1
+jmp four
+";
+        let problem = Problem {};
+        let e = problem.parse_input(raw_input.as_bytes()).err().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(3), Some(5)));
+        assert_eq!(e.text(), Some("jmp four"));
+
+        let e = Inst::from_string("hlt +1").err().unwrap();
+        assert_eq!(e.message(), "invalid instruction \"hlt\"");
     }
 }
@@ -1,61 +1,74 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use std::io;
+
+day_params! {
+    /// How many numbers before each one it must be the sum of two of.
+    preamble: usize = 25,
+}
 
 pub struct Problem;
 
 impl Problem {
-    fn first_invalid(&self, input: &[i64], n: usize) -> i64 {
-        let (_, num) = input
+    /// The first number that is not the sum of two of the `n` before it.
+    fn first_invalid(&self, input: &[i64], n: usize) -> Option<i64> {
+        input
             .iter()
             .enumerate()
+            .skip(n)
             .find(|&(ind, num)| {
                 let slice = &input[ind - n..ind];
-                ind >= n && !slice.iter().any(|i| slice.contains(&(num - i)))
+                !slice
+                    .iter()
+                    .any(|i| num.checked_sub(*i).is_some_and(|j| slice.contains(&j)))
             })
-            .unwrap();
-        *num
+            .map(|(_, &num)| num)
     }
 
-    fn encryption_weakness(&self, input: &[i64], n: usize) -> i64 {
-        let target_sum = self.first_invalid(input, n);
-        let (mut low_ind, mut high_ind, mut sum) = (0, 0, 0);
+    /// The sum of the smallest and largest number of a contiguous run of at least two
+    /// numbers adding up to the first invalid number.
+    fn encryption_weakness(&self, input: &[i64], n: usize) -> Option<i128> {
+        let target_sum = i128::from(self.first_invalid(input, n)?);
+        // Sums are kept as i128 so that no run of i64 numbers can overflow them.
+        let mut prefix_sums = vec![0i128];
+        for &num in input {
+            prefix_sums.push(prefix_sums.last().unwrap() + i128::from(num));
+        }
 
-        loop {
-            #[allow(clippy::clippy::comparison_chain)]
-            if sum < target_sum {
-                high_ind += 1;
-                sum += input[high_ind];
-            } else if sum > target_sum {
-                low_ind += 1;
-                sum -= input[low_ind];
-            } else {
-                let slice = &input[low_ind..=high_ind];
-                return slice.iter().max().unwrap() + slice.iter().min().unwrap();
+        for low_ind in 0..input.len() {
+            for high_ind in low_ind + 1..input.len() {
+                if prefix_sums[high_ind + 1] - prefix_sums[low_ind] == target_sum {
+                    trace!("run", first_line = low_ind + 1, last_line = high_ind + 1);
+                    let slice = &input[low_ind..=high_ind];
+                    let (min, max) = (slice.iter().min().unwrap(), slice.iter().max().unwrap());
+                    return Some(i128::from(*min) + i128::from(*max));
+                }
             }
         }
+        None
     }
 }
 
 impl Solver for Problem {
     type Input = Vec<i64>;
-    type Output1 = i64;
-    type Output2 = i64;
+    type Params = Params;
+    type Output1 = Option<i64>;
+    type Output2 = Option<i128>;
 
     fn get_day(&self) -> i32 {
         9
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines().flatten().flat_map(|s| s.parse()).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, parse_num)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        self.first_invalid(input, 25)
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1 {
+        self.first_invalid(input, params.preamble)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        self.encryption_weakness(input, 25)
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
+        self.encryption_weakness(input, params.preamble)
     }
 }
 
@@ -86,8 +99,11 @@ mod tests {
 309
 576";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.first_invalid(&input, 5), 127);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(
+            problem.solve_first(&input, &Params { preamble: 5 }),
+            Some(127)
+        );
     }
 
     #[test]
@@ -113,7 +129,10 @@ mod tests {
 309
 576";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.encryption_weakness(&input, 5), 62);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(
+            problem.solve_second(&input, &Params { preamble: 5 }),
+            Some(62)
+        );
     }
 }
@@ -1,62 +1,130 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use std::io;
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<i32>;
+    type Params = ();
     type Output1 = usize;
-    type Output2 = i64;
+    type Output2 = Option<i64>;
 
     fn get_day(&self) -> i32 {
         10
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        let mut input: Vec<i32> = r.lines().flatten().flat_map(|s| s.parse()).collect();
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let mut input: Vec<i32> = parse_lines(r, parse_num)?;
         input.push(0);
         input.sort_unstable();
-        input
+        Ok(input)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         let one_jolt_diffs = input
             .windows(2)
-            .filter(|window| window[1] - window[0] == 1)
+            .filter(|window| i64::from(window[1]) - i64::from(window[0]) == 1)
             .count();
 
         let three_jolt_diffs = input
             .windows(2)
-            .filter(|window| window[1] - window[0] == 3)
+            .filter(|window| i64::from(window[1]) - i64::from(window[0]) == 3)
             .count();
 
         one_jolt_diffs * (three_jolt_diffs + 1)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    /// Returns None if the number of arrangements does not fit in an i64.
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         let n = input.len();
         let mut result: Vec<i64> = vec![1; n];
+        let within_reach = |i: usize, j: usize| i64::from(input[i]) - i64::from(input[j]) < 4;
 
         for i in 1..n {
-            if input[i] - input[i - 1] < 4 {
+            if within_reach(i, i - 1) {
                 result[i] = result[i - 1];
             }
-            if i > 1 && input[i] - input[i - 2] < 4 {
-                result[i] += result[i - 2];
+            if i > 1 && within_reach(i, i - 2) {
+                result[i] = result[i].checked_add(result[i - 2])?;
             }
-            if i > 2 && input[i] - input[i - 3] < 4 {
-                result[i] += result[i - 3];
+            if i > 2 && within_reach(i, i - 3) {
+                result[i] = result[i].checked_add(result[i - 3])?;
             }
         }
 
-        result[n - 1]
+        Some(result[n - 1])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::random::Rng;
+
+    /// Counts the subsets of adapters that chain from the outlet to the highest adapter
+    /// in steps of at most 3 jolts.
+    fn brute_force_arrangements(adapters: &[i32]) -> i64 {
+        let mut sorted = adapters.to_vec();
+        sorted.sort_unstable();
+        let highest = *sorted.last().unwrap();
+        (0..1u32 << sorted.len())
+            .filter(|mask| {
+                let mut prev = 0;
+                for (ind, &adapter) in sorted.iter().enumerate() {
+                    if mask >> ind & 1 == 1 {
+                        if adapter - prev > 3 {
+                            return false;
+                        }
+                        prev = adapter;
+                    }
+                }
+                prev == highest
+            })
+            .count() as i64
+    }
+
+    /// Adapters one to three jolts apart, and the number of 1-jolt differences times the
+    /// number of 3-jolt differences (including the device's own).
+    fn generate(rng: &mut Rng) -> (String, usize) {
+        let (mut joltage, mut ones, mut threes) = (0, 0, 1);
+        let mut adapters: Vec<_> = (0..rng.range(1..16))
+            .map(|_| {
+                let diff = rng.range(1..4);
+                match diff {
+                    1 => ones += 1,
+                    3 => threes += 1,
+                    _ => {}
+                }
+                joltage += diff;
+                joltage.to_string()
+            })
+            .collect();
+        rng.shuffle(&mut adapters);
+        (adapters.join("\n"), ones * threes)
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let problem = Problem {};
+        for seed in 0..200 {
+            let (raw_input, differences) = generate(&mut Rng::new(seed));
+            let adapters: Vec<i32> = raw_input.lines().map(|l| l.parse().unwrap()).collect();
+            let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+            assert_eq!(
+                problem.solve_first(&input, &()),
+                differences,
+                "seed {}",
+                seed
+            );
+            assert_eq!(
+                problem.solve_second(&input, &()),
+                Some(brute_force_arrangements(&adapters)),
+                "seed {}",
+                seed
+            );
+        }
+    }
 
     #[test]
     fn test_first() {
@@ -92,8 +160,8 @@ mod tests {
 10
 3";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 220);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 220);
     }
 
     #[test]
@@ -130,7 +198,23 @@ mod tests {
 10
 3";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 19208);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), Some(19208));
+    }
+
+    #[test]
+    fn test_extremes() {
+        let problem = Problem {};
+        let input = problem
+            .parse_input(&b"-2147483648\n2147483647\n1\n"[..])
+            .unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 1);
+        assert_eq!(problem.solve_second(&input, &()), Some(1));
+        // The arrangements of 1..=n grow like the tribonacci numbers.
+        let raw_input: Vec<_> = (1..200).map(|i| i.to_string()).collect();
+        let input = problem
+            .parse_input(raw_input.join("\n").as_bytes())
+            .unwrap();
+        assert_eq!(problem.solve_second(&input, &()), None);
     }
 }
@@ -1,9 +1,13 @@
+use crate::automaton::{Automaton, Rule};
+use crate::error::ParseError;
+use crate::grid::{Cells, Grid, Neighbourhood, Point};
 use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use std::fmt;
+use std::io;
 
 pub struct Problem;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum State {
     Floor,
     Empty,
@@ -11,145 +15,139 @@ pub enum State {
 }
 
 impl State {
-    fn from_char(ch: char) -> Self {
+    fn from_char(ch: char) -> Result<Self, ParseError> {
         match ch {
-            '.' => Self::Floor,
-            '#' => Self::Empty,
-            'L' => Self::Occupied,
-            _ => panic!("Unrecognizable char"),
+            '.' => Ok(Self::Floor),
+            'L' => Ok(Self::Empty),
+            '#' => Ok(Self::Occupied),
+            _ => Err(ParseError::new(format!("unrecognizable char {:?}", ch))),
         }
     }
 }
 
-type Pos = (usize, usize);
-
-#[derive(Clone, PartialEq, Eq)]
-pub struct Model {
-    board: Vec<Vec<State>>,
-}
-
-impl Model {
-    fn new(board: Vec<Vec<State>>) -> Self {
-        Self { board }
-    }
-
-    fn width(&self) -> usize {
-        self.board[0].len()
-    }
-
-    fn height(&self) -> usize {
-        self.board.len()
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ch = match self {
+            Self::Floor => '.',
+            Self::Empty => 'L',
+            Self::Occupied => '#',
+        };
+        write!(f, "{}", ch)
     }
+}
 
-    fn get_signed(&self, pos: (isize, isize)) -> Option<State> {
-        let (x, y) = pos;
-        if x < 0 || y < 0 || x >= self.height() as isize || y >= self.width() as isize {
-            None
-        } else {
-            self.board
-                .get(x as usize)
-                .and_then(|row| row.get(y as usize))
-                .copied()
-        }
-    }
+/// Which seats a passenger considers when deciding to sit down or leave.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sight {
+    /// The eight seats next to theirs.
+    Adjacent,
+    /// The first seat seen in each of the eight directions, looking over the floor.
+    Visible,
+}
 
-    fn get(&self, pos: Pos) -> Option<State> {
-        self.get_signed((pos.0 as isize, pos.1 as isize))
-    }
+/// A rule set: passengers sit down when none of the seats they consider is occupied,
+/// and leave when at least `tolerance` of them are.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub sight: Sight,
+    pub tolerance: usize,
+}
 
-    fn get_adjacent(&self, pos: Pos) -> impl Iterator<Item = State> + '_ {
-        let (x, y) = (pos.0 as isize, pos.1 as isize);
+impl Rules {
+    /// The rules of part 1.
+    pub const ADJACENT: Self = Self {
+        sight: Sight::Adjacent,
+        tolerance: 4,
+    };
+    /// The rules of part 2.
+    pub const VISIBLE: Self = Self {
+        sight: Sight::Visible,
+        tolerance: 5,
+    };
+}
 
-        (-1..=1).flat_map(move |i| {
-            (-1..=1).filter_map(move |j| {
-                if i == 0 && j == 0 {
-                    None
-                } else {
-                    self.get_signed((x + i, y + j))
+impl Rule<Grid<State>> for Rules {
+    fn neighbours<'a>(
+        &'a self,
+        board: &'a Grid<State>,
+        pos: Point,
+    ) -> impl Iterator<Item = (Point, &'a State)> + 'a
+    where
+        State: 'a,
+    {
+        Neighbourhood::Eight
+            .offsets()
+            .iter()
+            .filter_map(move |&direction| {
+                let mut ray = board.ray(pos, direction);
+                match self.sight {
+                    Sight::Adjacent => ray.next(),
+                    Sight::Visible => ray.find(|(_, &state)| state != State::Floor),
                 }
             })
-        })
-    }
-
-    fn occupied_adjacent_count(&self, pos: Pos) -> usize {
-        self.get_adjacent(pos)
-            .filter(|&state| state == State::Occupied)
-            .count()
     }
 
-    fn new_state(&self, pos: Pos) -> State {
-        let state = self.get(pos).unwrap();
-        if state == State::Empty && self.occupied_adjacent_count(pos) == 0 {
-            State::Occupied
-        } else if state == State::Occupied && self.occupied_adjacent_count(pos) >= 4 {
-            State::Empty
-        } else {
-            state
+    fn new_state<'a>(
+        &self,
+        &state: &State,
+        neighbours: impl Iterator<Item = (Point, &'a State)>,
+    ) -> State
+    where
+        State: 'a,
+    {
+        let mut occupied = neighbours.filter(|(_, &state)| state == State::Occupied);
+        match state {
+            State::Empty if occupied.next().is_none() => State::Occupied,
+            State::Occupied if occupied.count() >= self.tolerance => State::Empty,
+            state => state,
         }
     }
+}
 
-    fn perform_one_round(&self) -> Self {
-        let board = (0..self.height())
-            .map(|x| (0..self.width()).map(|y| self.new_state((x, y))).collect())
-            .collect();
-        Self::new(board)
-    }
-
-    fn occupied_seats(&self) -> usize {
-        self.board
+impl Problem {
+    /// The number of seats taken once `rules` have been applied to `board` until nobody
+    /// moves any more.
+    fn settle(&self, board: &Grid<State>, rules: Rules) -> usize {
+        let mut automaton = Automaton::new(board.clone(), rules);
+        automaton.run(None);
+        trace!("settled", round = automaton.round());
+        automaton
+            .board()
             .iter()
-            .map(|row| {
-                row.iter()
-                    .filter(|&state| state == &State::Occupied)
-                    .count()
-            })
-            .sum()
+            .filter(|&(_, &state)| state == State::Occupied)
+            .count()
     }
 }
 
-impl Problem {}
-
 impl Solver for Problem {
-    type Input = Model;
+    type Input = Grid<State>;
+    type Params = ();
     type Output1 = usize;
-    type Output2 = i32;
+    type Output2 = usize;
 
     fn get_day(&self) -> i32 {
         11
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        let board = r
-            .lines()
-            .flatten()
-            .map(|l| l.chars().map(State::from_char).collect())
-            .collect();
-        Model::new(board)
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        Grid::parse(r, State::from_char)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut board = input.clone();
-        let mut new_board = board.perform_one_round();
-        while new_board != board {
-            board = new_board.clone();
-            new_board = board.perform_one_round();
-        }
-        new_board.occupied_seats()
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
+        self.settle(input, Rules::ADJACENT)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        0
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
+        self.settle(input, Rules::VISIBLE)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::InputSource;
 
-    #[test]
-    fn test_first() {
-        let raw_input = "L.LL.LL.LL
+    const EXAMPLE: &str = "L.LL.LL.LL
 LLLLLLL.LL
 L.L.L..L..
 LLLL.LL.LL
@@ -160,16 +158,84 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL
 ";
+
+    #[test]
+    fn test_first() {
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 37);
+        let input = problem.parse_input(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 37);
     }
 
     #[test]
     fn test_second() {
-        let raw_input = "";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 0);
+        let input = problem.parse_input(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), 26);
+    }
+
+    #[test]
+    fn test_rounds() {
+        let problem = Problem {};
+        let board = problem.parse_input(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(board.to_string(), EXAMPLE);
+        let mut automaton = Automaton::new(board, Rules::VISIBLE);
+        automaton.step();
+        automaton.step();
+        assert_eq!(
+            automaton.board().to_string(),
+            "#.LL.LL.L#
+#LLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLL#
+#.LLLLLL.L
+#.LLLLL.L#
+"
+        );
+    }
+
+    fn occupied_count(rules: Rules, board: &Grid<State>, pos: Point) -> usize {
+        rules
+            .neighbours(board, pos)
+            .filter(|(_, &state)| state == State::Occupied)
+            .count()
+    }
+
+    #[test]
+    fn test_visible() {
+        let problem = Problem {};
+        let board = problem
+            .parse_input(
+                ".......#.
+...#.....
+.#.......
+.........
+..#L....#
+....#....
+.........
+#........
+...#....."
+                    .as_bytes(),
+            )
+            .unwrap();
+        assert_eq!(occupied_count(Rules::VISIBLE, &board, (3, 4)), 8);
+        assert_eq!(occupied_count(Rules::ADJACENT, &board, (3, 4)), 2);
+
+        let board = problem
+            .parse_input(".............\n.L.L.#.#.#.#.\n.............".as_bytes())
+            .unwrap();
+        assert_eq!(occupied_count(Rules::VISIBLE, &board, (1, 1)), 0);
+        assert_eq!(occupied_count(Rules::VISIBLE, &board, (3, 1)), 1);
+    }
+
+    #[test]
+    fn test_input() {
+        let problem = Problem {};
+        let input = problem.load_input(&InputSource::Default).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 2283);
+        assert_eq!(problem.solve_second(&input, &()), 2054);
     }
 }
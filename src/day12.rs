@@ -1,6 +1,7 @@
-use crate::solver::Solver;
-use std::convert::{From, Into};
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use std::convert::{From, Into, TryFrom};
+use std::io;
 use std::ops::{AddAssign, SubAssign};
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -37,15 +38,23 @@ impl SubAssign<i64> for Direction {
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Command {
-    command: char,
-    num: i64,
+    pub command: char,
+    pub num: i64,
 }
 
-impl From<&str> for Command {
-    fn from(s: &str) -> Self {
-        let num: i64 = s.get(1..).unwrap().parse().unwrap();
-        let command = s.chars().next().unwrap();
-        Self { command, num }
+impl TryFrom<&str> for Command {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let command = s
+            .chars()
+            .next()
+            .ok_or_else(|| ParseError::new("empty command"))?;
+        if !"NESWLRF".contains(command) {
+            return Err(ParseError::new(format!("unexpected command {:?}", command)).at_column(1));
+        }
+        let num: i64 = parse_num(&s[command.len_utf8()..]).map_err(|e| e.at_column(2))?;
+        Ok(Self { command, num })
     }
 }
 
@@ -69,50 +78,63 @@ impl Ship {
         }
     }
 
-    fn perform_command_pt1(&mut self, command: Command) {
+    /// Moves the ship, or returns None if a coordinate overflows.
+    fn perform_command_pt1(&mut self, command: Command) -> Option<()> {
         let Command { command, num } = command;
+        let (x, y) = (&mut self.x, &mut self.y);
         match command {
-            'N' => self.y += num,
-            'E' => self.x += num,
-            'S' => self.y -= num,
-            'W' => self.x -= num,
+            'N' => *y = y.checked_add(num)?,
+            'E' => *x = x.checked_add(num)?,
+            'S' => *y = y.checked_sub(num)?,
+            'W' => *x = x.checked_sub(num)?,
             'L' => self.dir -= num,
             'R' => self.dir += num,
             'F' => match self.dir {
-                Direction::N => self.y += num,
-                Direction::E => self.x += num,
-                Direction::S => self.y -= num,
-                Direction::W => self.x -= num,
+                Direction::N => *y = y.checked_add(num)?,
+                Direction::E => *x = x.checked_add(num)?,
+                Direction::S => *y = y.checked_sub(num)?,
+                Direction::W => *x = x.checked_sub(num)?,
             },
             _ => panic!("Unexpected command"),
         }
+        Some(())
     }
 
-    fn perform_command_pt2(&mut self, command: Command) {
+    /// Moves the ship or its waypoint, or returns None if a coordinate overflows.
+    fn perform_command_pt2(&mut self, command: Command) -> Option<()> {
         let Command { command, num } = command;
+        let (x, y) = (&mut self.waypoint_x, &mut self.waypoint_y);
         match command {
-            'N' => self.waypoint_y += num,
-            'E' => self.waypoint_x += num,
-            'S' => self.waypoint_y -= num,
-            'W' => self.waypoint_x -= num,
-            'L' => {
-                let (temp_x, temp_y) =
-                    (0..num / 90).fold((self.waypoint_x, self.waypoint_y), |(x, y), _| (-y, x));
-                self.waypoint_x = temp_x;
-                self.waypoint_y = temp_y;
-            }
-            'R' => {
-                let (temp_x, temp_y) =
-                    (0..num / 90).fold((self.waypoint_x, self.waypoint_y), |(x, y), _| (y, -x));
-                self.waypoint_x = temp_x;
-                self.waypoint_y = temp_y;
+            'N' => *y = y.checked_add(num)?,
+            'E' => *x = x.checked_add(num)?,
+            'S' => *y = y.checked_sub(num)?,
+            'W' => *x = x.checked_sub(num)?,
+            'L' | 'R' => {
+                // Four quarter turns bring the waypoint back where it was.
+                let turns = (num / 90).rem_euclid(4);
+                let left = if command == 'L' {
+                    turns
+                } else {
+                    (4 - turns) % 4
+                };
+                for _ in 0..left {
+                    let (temp_x, temp_y) = (y.checked_neg()?, *x);
+                    *x = temp_x;
+                    *y = temp_y;
+                }
             }
             'F' => {
-                self.x += self.waypoint_x * num;
-                self.y += self.waypoint_y * num;
+                self.x = self.x.checked_add(x.checked_mul(num)?)?;
+                self.y = self.y.checked_add(y.checked_mul(num)?)?;
             }
             _ => panic!("Unexpected command"),
         }
+        Some(())
+    }
+
+    /// The Manhattan distance from the start, if it fits in an i64.
+    fn distance(&self) -> Option<i64> {
+        self.x.checked_abs()?.checked_add(self.y.checked_abs()?)
     }
 }
 
@@ -120,37 +142,34 @@ pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<Command>;
-    type Output1 = i64;
-    type Output2 = i64;
+    type Params = ();
+    type Output1 = Option<i64>;
+    type Output2 = Option<i64>;
 
     fn get_day(&self) -> i32 {
         12
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines()
-            .flatten()
-            .map(|l| Command::from(l.as_ref()))
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, |l| Command::try_from(l))
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         let mut ship = Ship::new();
         for command in input.iter() {
-            ship.perform_command_pt1(*command);
+            ship.perform_command_pt1(*command)?;
         }
 
-        ship.x.abs() + ship.y.abs()
+        ship.distance()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         let mut ship = Ship::new();
         for command in input.iter() {
-            ship.perform_command_pt2(*command);
+            ship.perform_command_pt2(*command)?;
         }
 
-        ship.x.abs() + ship.y.abs()
+        ship.distance()
     }
 }
 
@@ -167,8 +186,8 @@ R90
 F11
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 25);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), Some(25));
     }
 
     #[test]
@@ -180,7 +199,20 @@ R90
 F11
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 286);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), Some(286));
+    }
+
+    #[test]
+    fn test_extremes() {
+        let problem = Problem {};
+        let input = problem
+            .parse_input(&b"L900000000000000000\nR-270\nF7\n"[..])
+            .unwrap();
+        // 10^16 full turns, then a quarter turn right: the waypoint ends up at (1, -10).
+        assert_eq!(problem.solve_second(&input, &()), Some(77));
+        let input = problem.parse_input(&b"F9223372036854775807\n"[..]).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), Some(i64::MAX));
+        assert_eq!(problem.solve_second(&input, &()), None);
     }
 }
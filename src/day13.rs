@@ -1,58 +1,88 @@
-use crate::solver::Solver;
-use regex::Regex;
-use std::io::{self, BufRead, BufReader};
+use crate::error::ParseError;
+use crate::numtheory;
+use crate::solver::{parse_num, read_lines, Solver};
+use std::io;
 
 pub struct Input {
-    timestamp: i64,
-    routes: Vec<i64>,
+    pub timestamp: i64,
+    /// `(offset, bus_id)` for each bus in the schedule, the offset being its position.
+    pub buses: Vec<(i64, i64)>,
 }
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Input;
-    type Output1 = i64;
-    type Output2 = i64;
+    type Params = ();
+    type Output1 = Option<i64>;
+    type Output2 = Option<i128>;
 
     fn get_day(&self) -> i32 {
         13
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        let mut lines = r.lines().flatten();
-        let timestamp: i64 = lines.next().unwrap().trim().parse().unwrap();
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let lines = read_lines(r)?;
+        let timestamp_line = lines
+            .first()
+            .ok_or_else(|| ParseError::new("missing timestamp").at_line(1))?;
+        let timestamp: i64 = parse_num(timestamp_line).map_err(|e| e.in_line(1, timestamp_line))?;
+        if timestamp < 0 {
+            return Err(ParseError::new("negative timestamp").in_line(1, timestamp_line));
+        }
 
-        let regex = Regex::new(r"(\d+)").unwrap();
-        let routes: Vec<_> = regex
-            .captures_iter(&lines.next().unwrap())
-            .flat_map(|capture| capture[1].parse::<i64>())
-            .collect();
-        dbg!(&routes);
-        Input { routes, timestamp }
+        let buses_line = lines
+            .get(1)
+            .ok_or_else(|| ParseError::new("missing bus routes").at_line(2))?;
+        let mut column = 1;
+        let mut buses = Vec::new();
+        for (offset, s) in buses_line.split(',').enumerate() {
+            let in_line = |e: ParseError| e.at_column(column).in_line(2, buses_line);
+            if s.trim() != "x" {
+                match parse_num::<i64>(s).map_err(in_line)? {
+                    id if id <= 0 => {
+                        return Err(in_line(ParseError::new(format!("bus id {}", id))))
+                    }
+                    id => buses.push((offset as i64, id)),
+                }
+            }
+            column += s.len() + 1;
+        }
+        trace!("buses", offsets_and_ids = format!("{:?}", buses));
+        Ok(Input { timestamp, buses })
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let Input { timestamp, routes } = input;
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
+        let Input { timestamp, buses } = input;
 
-        let (route, wait_time) = routes
+        buses
             .iter()
-            .map(|&route| (route, (-timestamp).rem_euclid(route)))
+            .map(|&(_, id)| (id, (-timestamp).rem_euclid(id)))
             .min_by_key(|&(_, wait_time)| wait_time)
-            .unwrap();
-
-        route * wait_time
+            .and_then(|(id, wait_time)| {
+                trace!("earliest bus", id = id, wait = wait_time);
+                id.checked_mul(wait_time)
+            })
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        // TODO: Use Chinese Remainer Theorem: https://rosettacode.org/wiki/Chinese_remainder_theorem#Rust
-        0
+    /// The earliest timestamp at which each bus departs its offset in minutes later: the
+    /// solution of t + offset = 0 modulo each bus id.
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
+        let congruences: Vec<_> = input
+            .buses
+            .iter()
+            .map(|&(offset, id)| (-i128::from(offset), i128::from(id)))
+            .collect();
+        let (timestamp, period) = numtheory::crt(&congruences)?;
+        trace!("timestamp", t = timestamp, repeats_every = period);
+        Some(timestamp)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::InputSource;
 
     #[test]
     fn test_first() {
@@ -60,17 +90,61 @@ mod tests {
 7,13,x,x,59,x,31,19
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 295);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), Some(295));
     }
 
     #[test]
     fn test_second() {
-        let raw_input = "939
-7,13,x,x,59,x,31,19
-";
+        let schedules = [
+            ("7,13,x,x,59,x,31,19", 1068781),
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ];
+        let problem = Problem {};
+        for &(schedule, timestamp) in schedules.iter() {
+            let raw_input = format!("939\n{}\n", schedule);
+            let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+            assert_eq!(
+                problem.solve_second(&input, &()),
+                Some(timestamp),
+                "{}",
+                schedule
+            );
+        }
+    }
+
+    #[test]
+    fn test_input() {
+        let problem = Problem {};
+        let input = problem.load_input(&InputSource::Default).unwrap();
+        assert_eq!(input.buses.len(), 9);
+        assert_eq!(input.buses[2], (17, 983));
+        assert_eq!(problem.solve_first(&input, &()), Some(2382));
+        assert_eq!(problem.solve_second(&input, &()), Some(906332393333683));
+    }
+
+    #[test]
+    fn test_parse() {
+        let problem = Problem {};
+        let input = problem.parse_input(&b"939\nx,7,x,x,13\n"[..]).unwrap();
+        assert_eq!(input.buses, vec![(1, 7), (4, 13)]);
+
+        let e = problem.parse_input(&b"939\n7,x,0\n"[..]).err().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(2), Some(5)));
+        assert_eq!(e.message(), "bus id 0");
+        let e = problem.parse_input(&b"939\n7,y\n"[..]).err().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // The same bus cannot leave at two offsets that differ by less than its id.
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 0);
+        let input = problem.parse_input(&b"939\n7,7\n"[..]).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), None);
     }
 }
@@ -1,8 +1,8 @@
-use crate::solver::Solver;
-use regex::Regex;
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use regex::{Captures, Regex};
 use std::collections::HashMap;
-use std::io::{self, BufRead, BufReader};
-use std::str;
+use std::io;
 
 pub enum Command {
     Mask(String),
@@ -10,24 +10,88 @@ pub enum Command {
 }
 
 impl Command {
-    fn from_string(s: &str) -> Self {
-        let mask_regex = Regex::new(r"mask = ([X10]+)").unwrap();
+    fn from_string(s: &str) -> Result<Self, ParseError> {
+        let mask_regex = Regex::new(r"^mask = ([X10]{36})$").unwrap();
         let mem_regex = Regex::new(r"mem\[(?P<addr>\d+)\] = (?P<val>\d+)").unwrap();
+        let num = |capture: &Captures, name| {
+            let m = capture.name(name).unwrap();
+            match parse_num(m.as_str()) {
+                Ok(num) if num < 1 << 36 => Ok(num),
+                Ok(num) => Err(ParseError::new(format!("{} does not fit in 36 bits", num))),
+                Err(e) => Err(e),
+            }
+            .map_err(|e: ParseError| e.at_column(m.start() + 1))
+        };
+        let s = s.trim_end();
         if let Some(capture) = mask_regex.captures(s) {
-            Self::Mask(capture[1].to_string())
+            Ok(Self::Mask(capture[1].to_string()))
+        } else if let Some(mask) = s.strip_prefix("mask = ") {
+            let column = s.len() - mask.len() + 1;
+            Err(match mask.find(|ch| !"X10".contains(ch)) {
+                Some(ind) => {
+                    let ch = mask[ind..].chars().next().unwrap();
+                    ParseError::new(format!("unexpected char {:?} in mask", ch))
+                        .at_column(column + ind)
+                }
+                None => ParseError::new(format!("expected 36 mask bits, found {}", mask.len()))
+                    .at_column(column),
+            })
         } else if let Some(capture) = mem_regex.captures(s) {
-            Self::Write {
-                addr: capture["addr"].parse().unwrap(),
-                val: capture["val"].parse().unwrap(),
-            }
+            Ok(Self::Write {
+                addr: num(&capture, "addr")?,
+                val: num(&capture, "val")?,
+            })
         } else {
-            panic!("Unexpected command!");
+            Err(ParseError::new(
+                "expected \"mask = <mask>\" or \"mem[<addr>] = <value>\"",
+            ))
         }
     }
 }
 
 pub struct Problem;
 
+/// A set of addresses: the bits in `floating` take every value, the others are those of
+/// `fixed`, which has no floating bits set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Floating {
+    fixed: i64,
+    floating: i64,
+}
+
+impl Floating {
+    fn len(self) -> i128 {
+        1 << self.floating.count_ones()
+    }
+
+    /// The addresses of `self` that are not in `other`, as disjoint sets.
+    fn minus(self, other: Self) -> Vec<Self> {
+        let both_fixed = !(self.floating | other.floating);
+        if (self.fixed ^ other.fixed) & both_fixed != 0 {
+            return vec![self];
+        }
+        // Pin the bits `other` fixes one at a time: the opposite value gives a piece outside
+        // `other`, and the same value carries on towards the overlap, which is dropped.
+        let mut pieces = Vec::new();
+        let mut rest = self;
+        let mut split = self.floating & !other.floating;
+        while split != 0 {
+            let bit = split & -split;
+            split &= split - 1;
+            let floating = rest.floating & !bit;
+            pieces.push(Self {
+                fixed: rest.fixed | (!other.fixed & bit),
+                floating,
+            });
+            rest = Self {
+                fixed: rest.fixed | (other.fixed & bit),
+                floating,
+            };
+        }
+        pieces
+    }
+}
+
 impl Problem {
     fn get_masks_pt1(mask: &str) -> (i64, i64) {
         let and_mask = i64::from_str_radix(&mask.replace('X', "1"), 2).unwrap();
@@ -40,59 +104,41 @@ impl Problem {
         (num & and_mask) | or_mask
     }
 
-    fn apply_mask_pt2(s: &[u8], indices: &[usize]) -> Vec<i64> {
-        if indices.is_empty() {
-            let s = s.to_owned();
-            vec![i64::from_str_radix(str::from_utf8(&s).unwrap(), 2).unwrap()]
-        } else {
-            let first_index = indices[0];
-            [b'1', b'0']
-                .iter()
-                .flat_map(|ch| {
-                    let mut m = s.to_owned();
-                    m[first_index] = *ch;
-                    Self::apply_mask_pt2(&m, &indices[1..])
-                })
-                .collect()
+    fn mask_pt2(addr: i64, mask: &str) -> Floating {
+        let bits = |on: u8| {
+            mask.bytes()
+                .fold(0, |bits, ch| bits << 1 | i64::from(ch == on))
+        };
+        let floating = bits(b'X');
+        Floating {
+            fixed: (addr | bits(b'1')) & !floating,
+            floating,
         }
     }
-
-    fn mask_pt2(num: i64, mask: &str) -> Vec<i64> {
-        let num = format!("{:036b}", num);
-        let num = num.as_bytes();
-        let mask = mask.as_bytes();
-
-        let masked_num: Vec<_> = num
-            .iter()
-            .zip(mask.iter())
-            .map(|(&n, &m)| match m {
-                b'0' => n,
-                m => m,
-            })
-            .collect();
-        let indices: Vec<_> = (0..36).filter(|&i| masked_num[i] == b'X').collect();
-        Self::apply_mask_pt2(&masked_num, &indices)
-    }
 }
 
 impl Solver for Problem {
     type Input = Vec<Command>;
+    type Params = ();
     type Output1 = i64;
-    type Output2 = i64;
+    type Output2 = i128;
 
     fn get_day(&self) -> i32 {
         14
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines()
-            .flatten()
-            .map(|s| Command::from_string(&s))
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let mut has_mask = false;
+        parse_lines(r, |line| match Command::from_string(line)? {
+            Command::Write { .. } if !has_mask => Err(ParseError::new("write before any mask")),
+            command => {
+                has_mask |= matches!(command, Command::Mask(_));
+                Ok(command)
+            }
+        })
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         let mut mask = "";
         let mut address_space = HashMap::new();
         for command in input.iter() {
@@ -107,22 +153,32 @@ impl Solver for Problem {
         address_space.values().sum()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    /// Goes through the writes from the last one, counting only the addresses that no
+    /// later write covers, so that a mask's floating bits are never expanded.
+    fn solve_second(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
         let mut mask = "";
-        let mut address_space = HashMap::new();
+        let mut writes = Vec::new();
         for command in input.iter() {
             match command {
                 Command::Mask(s) => mask = s,
-                Command::Write { addr, val } => {
-                    let addr_list = Self::mask_pt2(*addr, mask);
-                    for new_addr in addr_list {
-                        address_space.insert(new_addr, *val);
-                    }
-                }
+                Command::Write { addr, val } => writes.push((Self::mask_pt2(*addr, mask), *val)),
             }
         }
 
-        address_space.values().sum()
+        let mut later: Vec<Floating> = Vec::new();
+        let mut sum = 0;
+        for &(addrs, val) in writes.iter().rev() {
+            let mut unwritten = vec![addrs];
+            for &other in later.iter() {
+                if unwritten.is_empty() {
+                    break;
+                }
+                unwritten = unwritten.into_iter().flat_map(|a| a.minus(other)).collect();
+            }
+            sum += i128::from(val) * unwritten.into_iter().map(Floating::len).sum::<i128>();
+            later.push(addrs);
+        }
+        sum
     }
 }
 
@@ -138,8 +194,8 @@ mem[7] = 101
 mem[8] = 0
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 165);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), 165);
     }
 
     #[test]
@@ -150,7 +206,62 @@ mask = 00000000000000000000000000000000X0XX
 mem[26] = 1
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 208);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), 208);
+    }
+
+    #[test]
+    fn test_floating() {
+        let raw_input = format!(
+            "mask = {}\nmem[0] = 5\nmask = {}\nmem[3] = 1\n",
+            "X".repeat(36),
+            "0".repeat(36)
+        );
+        let problem = Problem {};
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), 5 * ((1 << 36) - 1) + 1);
+
+        // {0, 1, 4, 5} minus {0, 1} is {4, 5}, and {0, 1} minus {0, 1, 4, 5} is empty.
+        let a = Problem::mask_pt2(0, "X0X");
+        let b = Problem::mask_pt2(0, "00X");
+        assert_eq!(a.minus(b), vec![Problem::mask_pt2(0b100, "00X")]);
+        assert_eq!(b.minus(a), vec![]);
+        // Disjoint sets are left alone.
+        let c = Problem::mask_pt2(0b010, "X1X");
+        assert_eq!(a.minus(c), vec![a]);
+    }
+
+    #[test]
+    fn test_parse_mask() {
+        let problem = Problem {};
+        let e = problem
+            .parse_input("\nmask = X\nmem[1] = 2\n".as_bytes())
+            .err()
+            .unwrap();
+        assert_eq!(e.message(), "expected 36 mask bits, found 1");
+        assert_eq!((e.line(), e.column()), (Some(2), Some(8)));
+
+        let e = problem
+            .parse_input("\nmem[1] = 2\nmask = X\n".as_bytes())
+            .err()
+            .unwrap();
+        assert_eq!(e.message(), "write before any mask");
+        assert_eq!(e.line(), Some(2));
+
+        let raw_input = format!("mask = {}\nmem[68719476736] = 1\n", "0".repeat(36));
+        let e = problem.parse_input(raw_input.as_bytes()).err().unwrap();
+        assert_eq!(e.message(), "68719476736 does not fit in 36 bits");
+        assert_eq!((e.line(), e.column()), (Some(2), Some(5)));
+
+        let long = format!("mask = {}\n", "X".repeat(70));
+        let e = problem.parse_input(long.as_bytes()).err().unwrap();
+        assert_eq!(e.message(), "expected 36 mask bits, found 70");
+
+        let e = problem
+            .parse_input("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XX2X0X\n".as_bytes())
+            .err()
+            .unwrap();
+        assert_eq!(e.message(), "unexpected char '2' in mask");
+        assert_eq!(e.column(), Some(40));
     }
 }
@@ -1,12 +1,25 @@
-use crate::solver::Solver;
+use crate::error::ParseError;
+use crate::solver::{parse_num, read_string, Solver};
 use std::collections::HashMap;
 use std::io;
 use std::iter::{successors, Iterator};
+use std::num::NonZeroUsize;
+
+day_params! {
+    /// The turn whose number part 1 asks for.
+    part1_turn: NonZeroUsize = NonZeroUsize::new(2020).unwrap(),
+    /// The turn whose number part 2 asks for.
+    part2_turn: NonZeroUsize = NonZeroUsize::new(30000000).unwrap(),
+}
 
 pub struct Problem;
 
 impl Problem {
-    fn solve_problem(&self, input: &[i64], target: usize) -> i64 {
+    fn solve_problem(&self, input: &[i64], target: NonZeroUsize) -> i64 {
+        let target = target.get();
+        if target <= input.len() {
+            return input[target - 1];
+        }
         let mut map = HashMap::new();
         for (ind, &num) in input[..input.len() - 1].iter().enumerate() {
             map.insert(num, ind);
@@ -25,6 +38,7 @@ impl Problem {
 
 impl Solver for Problem {
     type Input = Vec<i64>;
+    type Params = Params;
     type Output1 = i64;
     type Output2 = i64;
 
@@ -32,19 +46,29 @@ impl Solver for Problem {
         15
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let mut buf = "".to_string();
-        let mut r = r;
-        r.read_to_string(&mut buf).unwrap();
-        buf.split(',').map(|s| s.parse().unwrap()).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buf = read_string(r)?;
+        let line = buf.trim_end();
+        if line.is_empty() {
+            return Err(ParseError::new("no starting numbers").at_line(1));
+        }
+
+        let mut column = 1;
+        line.split(',')
+            .map(|s| {
+                let num = parse_num(s).map_err(|e| e.at_column(column).in_line(1, line));
+                column += s.len() + 1;
+                num
+            })
+            .collect()
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        self.solve_problem(input, 2020)
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1 {
+        self.solve_problem(input, params.part1_turn)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        self.solve_problem(input, 30000000)
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
+        self.solve_problem(input, params.part2_turn)
     }
 }
 
@@ -56,15 +80,22 @@ mod tests {
     fn test_first() {
         let raw_input = "0,3,6";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 436);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &Params::default()), 436);
+        for &(turn, number) in [(2, 3), (10, 0)].iter() {
+            let params = Params {
+                part1_turn: NonZeroUsize::new(turn).unwrap(),
+                ..Params::default()
+            };
+            assert_eq!(problem.solve_first(&input, &params), number);
+        }
     }
 
     #[test]
     fn test_second() {
         let raw_input = "0,3,6";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 175594);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &Params::default()), 175594);
     }
 }
@@ -1,15 +1,13 @@
-use crate::solver::Solver;
-use regex::Regex;
-use std::{
-    collections::btree_map::Range,
-    io::{self, BufRead, BufReader},
-};
+use crate::error::ParseError;
+use crate::solver::{parse_num, read_lines, Solver};
+use regex::{Captures, Regex};
+use std::io;
 use std::{collections::HashMap, ops::RangeInclusive};
 
 #[derive(Debug, Clone)]
 pub struct Field {
-    name: String,
-    ranges: Vec<RangeInclusive<i32>>,
+    pub name: String,
+    pub ranges: Vec<RangeInclusive<i32>>,
 }
 
 impl Field {
@@ -30,7 +28,7 @@ impl Field {
 
 #[derive(Debug, Clone)]
 pub struct Ticket {
-    values: Vec<i32>,
+    pub values: Vec<i32>,
 }
 
 impl Ticket {
@@ -50,9 +48,9 @@ impl Ticket {
 }
 
 pub struct Note {
-    fields: Vec<Field>,
-    ticket: Ticket,
-    neighbors: Vec<Ticket>,
+    pub fields: Vec<Field>,
+    pub ticket: Ticket,
+    pub neighbors: Vec<Ticket>,
 }
 
 impl Note {
@@ -64,50 +62,67 @@ impl Note {
             .collect()
     }
 
-    fn column_mapping(&self) -> HashMap<String, usize> {
-        let mut result = HashMap::new();
-        let mut fields = self.fields.clone();
+    /// Maps each field to its column by repeatedly settling a field that only one of the
+    /// remaining columns can hold. Returns None if the tickets don't pin down every field.
+    fn column_mapping(&self) -> Option<HashMap<String, usize>> {
         let valid_tickets = self.valid_tickets();
-
-        for i in 0..self.fields.len() {
-            let chosen_field = fields.remove(
-                fields
-                    .iter()
-                    .position(|field| {
+        let mut candidates: Vec<Vec<usize>> = self
+            .fields
+            .iter()
+            .map(|field| {
+                (0..self.fields.len())
+                    .filter(|&i| {
                         valid_tickets
                             .iter()
-                            .map(|neighbor| neighbor.values[i])
-                            .all(|val| field.is_value_valid(val))
+                            .all(|ticket| field.is_value_valid(ticket.values[i]))
                     })
-                    .unwrap(),
-            );
+                    .collect()
+            })
+            .collect();
 
-            result.insert(chosen_field.name, i);
+        let mut result = HashMap::new();
+        while result.len() < self.fields.len() {
+            let (field, column) = candidates
+                .iter()
+                .enumerate()
+                .find(|(_, columns)| columns.len() == 1)
+                .map(|(field, columns)| (field, columns[0]))?;
+            trace!("column", field = self.fields[field].name, column = column);
+            result.insert(self.fields[field].name.clone(), column);
+            for columns in candidates.iter_mut() {
+                columns.retain(|&i| i != column);
+            }
         }
-        result
+        Some(result)
     }
 
-    fn scanning_error_rate(&self) -> i32 {
+    fn scanning_error_rate(&self) -> i64 {
         self.neighbors
             .iter()
             .flat_map(|ticket| ticket.invalid_values(&self.fields))
+            .map(i64::from)
             .sum()
     }
 }
 
+day_params! {
+    /// The prefix of the fields whose values part 2 multiplies.
+    prefix: String = "departure".to_string(),
+}
+
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Note;
-    type Output1 = i32;
-    type Output2 = i32;
+    type Params = Params;
+    type Output1 = i64;
+    type Output2 = Option<i64>;
 
     fn get_day(&self) -> i32 {
         16
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
         let field_regex = Regex::new(
             r"(?P<name>[a-z ]+): (?P<lower1>\d+)-(?P<higher1>\d+) or (?P<lower2>\d+)-(?P<higher2>\d+)",
         ).unwrap();
@@ -115,23 +130,44 @@ impl Solver for Problem {
         let mut neighbors = Vec::new();
         let mut my_ticket = Ticket { values: vec![] };
         let mut is_my_ticket = false;
-        for line in r.lines() {
-            let line = line.unwrap();
+        let num = |captures: &Captures, name| {
+            let m = captures.name(name).unwrap();
+            parse_num(m.as_str()).map_err(|e: ParseError| e.at_column(m.start() + 1))
+        };
+        let ticket_values = |line: &str| {
+            let mut column = 1;
+            line.split(',')
+                .map(|s| {
+                    let value = parse_num(s).map_err(|e| e.at_column(column));
+                    column += s.len() + 1;
+                    value
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
 
-            if let Some(captures) = field_regex.captures(&line) {
+        for (ind, line) in read_lines(r)?.iter().enumerate() {
+            let in_line = |e: ParseError| e.in_line(ind + 1, line);
+            if let Some(captures) = field_regex.captures(line) {
                 fields.push(Field::new(
                     &captures["name"],
-                    captures["lower1"].parse().unwrap(),
-                    captures["higher1"].parse().unwrap(),
-                    captures["lower2"].parse().unwrap(),
-                    captures["higher2"].parse().unwrap(),
+                    num(&captures, "lower1").map_err(in_line)?,
+                    num(&captures, "higher1").map_err(in_line)?,
+                    num(&captures, "lower2").map_err(in_line)?,
+                    num(&captures, "higher2").map_err(in_line)?,
                 ))
             } else if line.contains("your ticket") {
                 is_my_ticket = true;
             } else if line.contains("nearby tickets") {
                 is_my_ticket = false;
             } else if !line.is_empty() {
-                let values = line.split(',').map(|s| s.parse().unwrap()).collect();
+                let values = ticket_values(line).map_err(in_line)?;
+                if values.len() != fields.len() {
+                    return Err(in_line(ParseError::new(format!(
+                        "expected {} values, found {}",
+                        fields.len(),
+                        values.len()
+                    ))));
+                }
                 let ticket = Ticket { values };
                 if is_my_ticket {
                     my_ticket = ticket;
@@ -141,24 +177,24 @@ impl Solver for Problem {
             }
         }
 
-        Note {
+        Ok(Note {
             fields,
             neighbors,
             ticket: my_ticket,
-        }
+        })
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
         input.scanning_error_rate()
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2 {
         input
-            .column_mapping()
+            .column_mapping()?
             .iter()
-            .filter(|(key, _i)| key.starts_with("departure"))
-            .map(|(_key, i)| input.ticket.values[*i])
-            .product()
+            .filter(|(key, _i)| key.starts_with(&params.prefix))
+            .filter_map(|(_key, &i)| input.ticket.values.get(i).copied())
+            .try_fold(1i64, |product, value| product.checked_mul(i64::from(value)))
     }
 }
 
@@ -182,8 +218,8 @@ nearby tickets:
 38,6,12
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 71);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &Params::default()), 71);
     }
 
     #[test]
@@ -201,16 +237,22 @@ nearby tickets:
 5,14,9
 ";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
         assert_eq!(
             input.column_mapping(),
-            vec![
-                ("row".to_string(), 0),
-                ("class".to_string(), 1),
-                ("seat".to_string(), 2),
-            ]
-            .into_iter()
-            .collect()
+            Some(
+                vec![
+                    ("row".to_string(), 0),
+                    ("class".to_string(), 1),
+                    ("seat".to_string(), 2),
+                ]
+                .into_iter()
+                .collect()
+            )
         );
+        let params = Params {
+            prefix: "s".to_string(),
+        };
+        assert_eq!(problem.solve_second(&input, &params), Some(13));
     }
 }
@@ -0,0 +1,176 @@
+use std::{error, fmt, io};
+
+/// An error in the puzzle input, located as precisely as the parser could manage.
+///
+/// Parsers usually only know the message and maybe a column; the line, the offending
+/// text and the day are filled in on the way out by `parse_lines` and `Solver::load_input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: Option<i32>,
+    line: Option<usize>,
+    column: Option<usize>,
+    text: Option<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column: None,
+            text: None,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the 1-based column, unless a more precise one was already set.
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    /// Sets the 1-based line number, unless it was already set.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Sets the 1-based line number and its text, unless they were already set.
+    pub fn in_line(mut self, line: usize, text: &str) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+            self.text = Some(text.to_string());
+        }
+        self
+    }
+
+    pub fn for_day(mut self, day: i32) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+
+    pub fn day(&self) -> Option<i32> {
+        self.day
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = Vec::new();
+        if let Some(day) = self.day {
+            location.push(format!("day {}", day));
+        }
+        if let Some(line) = self.line {
+            location.push(format!("line {}", line));
+        }
+        if let Some(column) = self.column {
+            location.push(format!("column {}", column));
+        }
+
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(text) = &self.text {
+            write!(f, " in {:?}", text)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        Self::new(format!("unable to read input: {}", e))
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    /// A parameter override that the day does not have or cannot parse.
+    Param(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "unable to open input file: {}", e),
+            Self::Parse(e) => write!(f, "invalid input: {}", e),
+            Self::Param(message) => f.write_str(message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Param(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let e = ParseError::new("invalid number")
+            .at_column(3)
+            .in_line(2, "1-x a: abc")
+            .for_day(2);
+        assert_eq!(
+            e.to_string(),
+            "day 2, line 2, column 3: invalid number in \"1-x a: abc\""
+        );
+        assert_eq!(e.day(), Some(2));
+        assert_eq!(e.message(), "invalid number");
+        assert_eq!(ParseError::new("empty input").to_string(), "empty input");
+    }
+
+    #[test]
+    fn test_innermost_location_wins() {
+        let e = ParseError::new("bad")
+            .at_column(5)
+            .at_column(1)
+            .in_line(3, "inner")
+            .in_line(1, "outer");
+        assert_eq!(e.column(), Some(5));
+        assert_eq!(e.line(), Some(3));
+        assert_eq!(e.text(), Some("inner"));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::registry::DynSolver;
+use crate::solver::{Overrides, Part};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const EXAMPLES_DIR: &str = "examples";
+const SEPARATOR: &str = "---";
+
+/// A puzzle example: `key: value` header lines, a `---` line, then the input verbatim.
+///
+/// `part1` and `part2` give the expected answers; any other key is a parameter for the
+/// day, such as `preamble: 5` for day 9.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Example {
+    pub name: String,
+    pub expected: Vec<(Part, String)>,
+    pub params: Overrides,
+    pub input: String,
+}
+
+impl Example {
+    pub fn parse(name: &str, content: &str) -> Result<Self, String> {
+        let invalid = |message: String| format!("{}: {}", name, message);
+        let mut expected = Vec::new();
+        let mut params = Overrides::new();
+        let mut lines = content.split_inclusive('\n');
+        let mut offset = 0;
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| invalid(format!("missing {:?} line", SEPARATOR)))?;
+            offset += line.len();
+            let line = line.trim_end();
+            if line == SEPARATOR {
+                break;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("invalid header line {:?}", line)))?;
+            let (key, value) = (key.trim(), value.trim().to_string());
+            match key {
+                "part1" => expected.push((Part::One, value)),
+                "part2" => expected.push((Part::Two, value)),
+                _ => {
+                    params.insert(key.to_string(), value);
+                }
+            }
+        }
+        if expected.is_empty() {
+            return Err(invalid("no expected answer".to_string()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            expected,
+            params,
+            input: content[offset..].to_string(),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(p: P) -> Result<Self, String> {
+        let p = p.as_ref();
+        let content =
+            fs::read_to_string(p).map_err(|e| format!("Unable to read {}: {}", p.display(), e))?;
+        Self::parse(&p.display().to_string(), &content)
+    }
+
+    /// Solves the example with `entry` and describes any answer that differs from the
+    /// expected one.
+    pub fn check(&self, entry: &dyn DynSolver) -> Result<(), String> {
+        let parts: Vec<_> = self.expected.iter().map(|(part, _)| *part).collect();
+        let answers = entry
+            .solve_example(&mut self.input.as_bytes(), &parts, &self.params)
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+        let mismatches: Vec<_> = self
+            .expected
+            .iter()
+            .zip(answers)
+            .filter(|((_, expected), answer)| *expected != answer.to_string())
+            .map(|((part, expected), answer)| {
+                format!(
+                    "{} part {}: expected {}, got {}",
+                    self.name, part, expected, answer
+                )
+            })
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches.join("\n"))
+        }
+    }
+}
+
+pub fn day_dir(day: i32) -> PathBuf {
+    Path::new(EXAMPLES_DIR).join(format!("day{:02}", day))
+}
+
+/// Loads every example of `day`, sorted by file name; a day without examples has none.
+pub fn load_day(day: i32) -> Result<Vec<Example>, String> {
+    let dir = day_dir(day);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Unable to read {}: {}", dir.display(), e)),
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(|e| format!("Unable to read {}: {}", dir.display(), e))?;
+    paths.sort();
+    paths.iter().map(Example::load).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use crate::runner;
+
+    #[test]
+    fn test_parse() {
+        let example = Example::parse("ex", "part2: 62\npreamble: 5\n---\n35\n\n20\n").unwrap();
+        assert_eq!(example.expected, vec![(Part::Two, "62".to_string())]);
+        assert_eq!(
+            example.params.get("preamble").map(String::as_str),
+            Some("5")
+        );
+        assert_eq!(example.input, "35\n\n20\n");
+
+        assert!(Example::parse("ex", "part1: 1\n35\n").is_err());
+        assert!(Example::parse("ex", "preamble: 5\n---\n35\n").is_err());
+        assert!(Example::parse("ex", "part1 1\n---\n35\n").is_err());
+    }
+
+    #[test]
+    fn test_examples() {
+        let registry = registry();
+        let mut count = 0;
+        let mut failures = Vec::new();
+        runner::quiet_panics(|| {
+            for entry in registry.days() {
+                let examples = load_day(entry.day()).unwrap();
+                count += examples.len();
+                for example in examples {
+                    if let Err(e) = runner::guard(|| example.check(entry)) {
+                        failures.push(e);
+                    }
+                }
+            }
+        });
+        assert!(count > 0, "no examples found in {}", EXAMPLES_DIR);
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
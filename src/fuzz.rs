@@ -0,0 +1,276 @@
+//! Robustness harness: every day's `parse_input` and solvers run on random and mutated
+//! inputs must not panic, hang or allocate without bound. Inputs that fail are saved under
+//! `corpus/dayNN/` and replayed by every later run.
+
+use crate::alloc;
+use crate::example;
+use crate::random::Rng;
+use crate::registry;
+use crate::runner;
+use crate::solver::{Overrides, Part};
+use crate::verify::input_hash;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const CORPUS_DIR: &str = "corpus";
+const TIMEOUT: Duration = Duration::from_secs(10);
+const POLL: Duration = Duration::from_millis(10);
+const MAX_LIVE_BYTES: u64 = 512 << 20;
+const ITERATIONS: u64 = 150;
+
+/// Parts that are expensive for any input by design, and only get their input parsed:
+/// day 15 part 2 plays 30 million turns.
+const SLOW_PARTS: [(i32, Part); 1] = [(15, Part::Two)];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Failure {
+    Panic(String),
+    Hang,
+    RunawayAllocation,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(message) => write!(f, "panicked: {}", message),
+            Self::Hang => write!(f, "still running after {:?}", TIMEOUT),
+            Self::RunawayAllocation => write!(f, "held more than {} bytes", MAX_LIVE_BYTES),
+        }
+    }
+}
+
+fn parts(day: i32) -> Vec<Part> {
+    Part::BOTH
+        .iter()
+        .copied()
+        .filter(|&part| !SLOW_PARTS.contains(&(day, part)))
+        .collect()
+}
+
+/// Parses `input` and solves it in a worker thread. Parse errors are fine; a failing
+/// case leaves its worker running, stalled by the allocator if it went past the limit.
+pub fn run_case(day: i32, input: &[u8]) -> Result<(), Failure> {
+    let (tx, rx) = mpsc::channel();
+    let input = input.to_vec();
+    let stalled = alloc::stalled();
+    thread::spawn(move || {
+        let registry = registry();
+        let entry = registry.get(day).unwrap();
+        let parts = parts(day);
+        alloc::limit_thread(MAX_LIVE_BYTES);
+        let result = runner::quiet_panics(|| {
+            runner::guard(|| {
+                let _ = entry.solve_example(&mut &input[..], &parts, &Overrides::new());
+                Ok::<_, String>(())
+            })
+        });
+        let _ = tx.send(result);
+    });
+
+    let start = Instant::now();
+    loop {
+        match rx.recv_timeout(POLL) {
+            Ok(result) => {
+                return result.map_err(|e| Failure::Panic(e.replacen("panicked: ", "", 1)))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(Failure::Panic("worker exited".to_string()))
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+        if alloc::stalled() > stalled {
+            return Err(Failure::RunawayAllocation);
+        }
+        if start.elapsed() > TIMEOUT {
+            return Err(Failure::Hang);
+        }
+    }
+}
+
+const TOKENS: [&[u8]; 16] = [
+    b"0",
+    b"-1",
+    b"+",
+    b"x",
+    b"\n",
+    b"\n\n",
+    b"2147483647",
+    b"2147483648",
+    b"-2147483648",
+    b"4294967296",
+    b"9223372036854775807",
+    b"-9223372036854775808",
+    b"900000000000000000",
+    b"99999999999999999999",
+    b"340282366920938463463374607431768211456",
+    b"\xff",
+];
+
+/// Returns `seed` with a few random edits: bit flips, byte replacements, deleted,
+/// duplicated or swapped ranges, inserted numbers and separators, boundary integers
+/// replacing a number, long runs of digits, and bytes repeated into long runs, which
+/// stretch fixed-width fields such as day 14's masks.
+pub fn mutate(rng: &mut Rng, seed: &[u8]) -> Vec<u8> {
+    let mut bytes = seed.to_vec();
+    for _ in 0..rng.range(1..5) {
+        let len = bytes.len() as i64;
+        let pos = rng.range(0..len + 1) as usize;
+        let end = (pos + rng.range(0..16) as usize).min(bytes.len());
+        match rng.below(10) {
+            0 if pos < bytes.len() => bytes[pos] ^= 1 << rng.below(8),
+            1 if pos < bytes.len() => bytes[pos] = *rng.choose(b"0123456789-+x,:. \n#LF"),
+            2 => {
+                bytes.drain(pos..end);
+            }
+            3 => {
+                let copy = bytes[pos..end].to_vec();
+                bytes.splice(pos..pos, copy);
+            }
+            4 => {
+                let token = rng.choose(&TOKENS);
+                bytes.splice(pos..pos, token.iter().copied());
+            }
+            5 => bytes.truncate(pos),
+            6 if pos < bytes.len() => {
+                let run = vec![bytes[pos]; rng.range(1..64) as usize];
+                bytes.splice(pos..pos, run);
+            }
+            7 => {
+                let digits: Vec<_> = (0..rng.range(10..40))
+                    .map(|_| *rng.choose(b"0123456789"))
+                    .collect();
+                bytes.splice(pos..pos, digits);
+            }
+            8 => {
+                // The digits around `pos`, with a sign, make way for a boundary integer.
+                let is_digit = |b: &u8| b.is_ascii_digit() || *b == b'-';
+                let start = pos
+                    - bytes[..pos]
+                        .iter()
+                        .rev()
+                        .take_while(|b| is_digit(b))
+                        .count();
+                let end = pos + bytes[pos..].iter().take_while(|b| is_digit(b)).count();
+                let token = rng.choose(&TOKENS[6..15]);
+                bytes.splice(start..end, token.iter().copied());
+            }
+            _ => {
+                let other = rng.range(0..len + 1) as usize;
+                let (a, b) = (pos.min(other), pos.max(other));
+                bytes[a..b].reverse();
+            }
+        }
+    }
+    bytes
+}
+
+fn random_bytes(rng: &mut Rng) -> Vec<u8> {
+    (0..rng.range(0..64))
+        .map(|_| match rng.below(4) {
+            0 => rng.below(256) as u8,
+            _ => *rng.choose(b"0123456789-+x,:. \n#L"),
+        })
+        .collect()
+}
+
+fn corpus_dir(day: i32) -> PathBuf {
+    Path::new(CORPUS_DIR).join(format!("day{:02}", day))
+}
+
+/// The saved failing inputs of `day`.
+pub fn load_corpus(day: i32) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut paths: Vec<_> = match fs::read_dir(corpus_dir(day)) {
+        Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let bytes = fs::read(&path).unwrap();
+            (path, bytes)
+        })
+        .collect()
+}
+
+fn save_case(day: i32, input: &[u8]) -> PathBuf {
+    let path = corpus_dir(day).join(format!("{:016x}", input_hash(input)));
+    fs::create_dir_all(corpus_dir(day)).unwrap();
+    fs::write(&path, input).unwrap();
+    path
+}
+
+/// Seeds for mutation: the day's examples and its puzzle input, when present.
+fn seeds(day: i32) -> Vec<Vec<u8>> {
+    let mut seeds: Vec<_> = example::load_day(day)
+        .unwrap()
+        .into_iter()
+        .map(|example| example.input.into_bytes())
+        .collect();
+    if let Ok(input) = fs::read(format!("input/day{:02}", day)) {
+        seeds.push(input);
+    }
+    seeds
+}
+
+/// Replays the corpus of `day`, then runs `iterations` random cases, saving new failures
+/// to the corpus. Stops at the first failure, since its worker may still be running.
+pub fn fuzz_day(day: i32, seed: u64, iterations: u64) -> Result<(), String> {
+    for (path, input) in load_corpus(day) {
+        run_case(day, &input).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    let seeds = seeds(day);
+    let mut rng = Rng::new(seed ^ day as u64);
+    for _ in 0..iterations {
+        let input = if seeds.is_empty() || rng.chance(1, 5) {
+            random_bytes(&mut rng)
+        } else {
+            let seed = rng.choose(&seeds).clone();
+            mutate(&mut rng, &seed)
+        };
+        if let Err(e) = run_case(day, &input) {
+            let path = save_case(day, &input);
+            return Err(format!("{}: {} (saved)", path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutate() {
+        let mut rng = Rng::new(1);
+        let seed = b"1-3 a: abcde\n1-3 b: cdefg\n";
+        let mutants: Vec<_> = (0..20).map(|_| mutate(&mut rng, seed)).collect();
+        assert!(mutants.iter().any(|m| m != seed));
+        assert_eq!(mutants, {
+            let mut rng = Rng::new(1);
+            (0..20).map(|_| mutate(&mut rng, seed)).collect::<Vec<_>>()
+        });
+    }
+
+    #[test]
+    fn test_run_case() {
+        assert_eq!(run_case(1, b"1\n2\n"), Ok(()));
+        assert_eq!(run_case(1, b"not a number"), Ok(()));
+    }
+
+    #[test]
+    fn test_robustness() {
+        let failures: Vec<_> = runner::quiet_panics(|| {
+            registry()
+                .days()
+                .filter_map(|entry| fuzz_day(entry.day(), 0x5eed, ITERATIONS).err())
+                .collect()
+        });
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
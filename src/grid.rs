@@ -0,0 +1,424 @@
+//! Two-dimensional maps parsed from the puzzles' character drawings.
+//!
+//! [`Grid`] is a dense, bounded map. [`Grid::toroidal`] views it as repeating in every
+//! direction, and [`SparseGrid`] is an unbounded map that stores only the cells that differ
+//! from a default. All three implement [`Cells`], which provides neighbourhoods and rays.
+
+use crate::error::ParseError;
+use crate::solver::parse_lines;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::iter::successors;
+use std::ops::{Index, IndexMut};
+
+/// A position as `(x, y)`: the column, then the row counted downwards from the top.
+pub type Point = (i64, i64);
+
+fn add(p: Point, offset: Point) -> Option<Point> {
+    Some((p.0.checked_add(offset.0)?, p.1.checked_add(offset.1)?))
+}
+
+/// The cells counted as next to a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Up, down, left and right.
+    Four,
+    /// The four, plus the diagonals.
+    Eight,
+}
+
+impl Neighbourhood {
+    /// Offsets of the neighbours, in reading order.
+    pub fn offsets(self) -> &'static [Point] {
+        match self {
+            Self::Four => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Self::Eight => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// Cell lookup shared by the grid variants.
+pub trait Cells {
+    type Cell;
+
+    /// The cell at `p`, or None outside the grid.
+    fn cell(&self, p: Point) -> Option<&Self::Cell>;
+
+    /// The neighbours of `p` that are inside the grid, with their positions.
+    fn neighbours(
+        &self,
+        p: Point,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (Point, &Self::Cell)> {
+        neighbourhood.offsets().iter().filter_map(move |&offset| {
+            let q = add(p, offset)?;
+            Some((q, self.cell(q)?))
+        })
+    }
+
+    /// The cells met walking from `p` in steps of `direction`, leaving out `p` itself,
+    /// until the walk leaves the grid. Positions are the ones walked through, so on a
+    /// toroidal or sparse grid the walk never ends by itself.
+    fn ray(&self, p: Point, direction: Point) -> impl Iterator<Item = (Point, &Self::Cell)> {
+        successors(add(p, direction), move |&q| add(q, direction))
+            .map_while(move |q| Some((q, self.cell(q)?)))
+    }
+}
+
+/// A dense rectangular map of cells, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its cells in row-major order.
+    ///
+    /// Panics if there are not `width * height` cells.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "wrong number of cells");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_fn<F: FnMut(Point) -> T>(width: usize, height: usize, f: F) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x as i64, y as i64)))
+            .map(f)
+            .collect();
+        Self::new(width, height, cells)
+    }
+
+    /// Parses a character map, turning each character into a cell with `f`. Blank lines
+    /// and trailing whitespace are skipped, and every row must have the same length.
+    pub fn parse<R, F>(r: R, mut f: F) -> Result<Self, ParseError>
+    where
+        R: io::Read,
+        F: FnMut(char) -> Result<T, ParseError>,
+    {
+        let mut width = None;
+        let rows = parse_lines(r, |line| {
+            let row: Vec<_> = line
+                .trim_end()
+                .chars()
+                .enumerate()
+                .map(|(col, ch)| f(ch).map_err(|e| e.at_column(col + 1)))
+                .collect::<Result<_, _>>()?;
+            match *width.get_or_insert(row.len()) {
+                len if len == row.len() => Ok(row),
+                len => Err(ParseError::new(format!(
+                    "expected {} cells, found {}",
+                    len,
+                    row.len()
+                ))),
+            }
+        })?;
+        let height = rows.len();
+        Ok(Self::new(
+            width.unwrap_or(0),
+            height,
+            rows.into_iter().flatten().collect(),
+        ))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        let (x, y) = p;
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            None
+        } else {
+            Some(y as usize * self.width + x as usize)
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.offset(p).is_some()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|ind| &self.cells[ind])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(move |ind| &mut self.cells[ind])
+    }
+
+    /// Every cell with its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(ind, cell)| (((ind % width) as i64, (ind / width) as i64), cell))
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U, F: FnMut(Point, &T) -> U>(&self, mut f: F) -> Grid<U> {
+        Grid::new(
+            self.width,
+            self.height,
+            self.iter().map(|(p, cell)| f(p, cell)).collect(),
+        )
+    }
+
+    /// Like [`Grid::map`], but writes into `target`, reusing its storage.
+    pub fn map_into<U, F: FnMut(Point, &T) -> U>(&self, target: &mut Grid<U>, mut f: F) {
+        target.width = self.width;
+        target.height = self.height;
+        target.cells.clear();
+        target.cells.extend(self.iter().map(|(p, cell)| f(p, cell)));
+    }
+
+    /// A view of the grid repeated endlessly in every direction.
+    pub fn toroidal(&self) -> Toroidal<'_, T> {
+        Toroidal(self)
+    }
+}
+
+impl<T> Cells for Grid<T> {
+    type Cell = T;
+
+    fn cell(&self, p: Point) -> Option<&T> {
+        self.get(p)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.get(p) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside the grid", p),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        match self.offset(p) {
+            Some(ind) => &mut self.cells[ind],
+            None => panic!("{:?} is outside the grid", p),
+        }
+    }
+}
+
+/// Renders each row on its own line, each cell with its `Display`.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A grid repeated endlessly in every direction, as returned by [`Grid::toroidal`].
+#[derive(Clone, Copy, Debug)]
+pub struct Toroidal<'a, T>(&'a Grid<T>);
+
+impl<T> Cells for Toroidal<'_, T> {
+    type Cell = T;
+
+    /// The cell at `p` wrapped into the grid; None only if the grid is empty.
+    fn cell(&self, p: Point) -> Option<&T> {
+        let Toroidal(grid) = self;
+        if grid.cells.is_empty() {
+            return None;
+        }
+        grid.get((
+            p.0.rem_euclid(grid.width as i64),
+            p.1.rem_euclid(grid.height as i64),
+        ))
+    }
+}
+
+/// An unbounded grid in which every cell starts out as `default`; only the others are
+/// stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    default: T,
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+        }
+    }
+
+    /// The cells of `grid` at the same positions, on an unbounded grid of `default`.
+    pub fn from_grid(grid: &Grid<T>, default: T) -> Self {
+        let mut sparse = Self::new(default);
+        for (p, cell) in grid.iter() {
+            sparse.set(p, cell.clone());
+        }
+        sparse
+    }
+
+    pub fn get(&self, p: Point) -> &T {
+        self.cells.get(&p).unwrap_or(&self.default)
+    }
+
+    pub fn set(&mut self, p: Point, cell: T) {
+        if cell == self.default {
+            self.cells.remove(&p);
+        } else {
+            self.cells.insert(p, cell);
+        }
+    }
+
+    /// The cells that differ from the default, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&p, cell)| (p, cell))
+    }
+
+    /// Resets every cell to the default.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The top-left and bottom-right corners of the smallest rectangle holding every
+    /// stored cell, or None if there are none.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut points = self.cells.keys();
+        let &first = points.next()?;
+        Some(points.fold((first, first), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        }))
+    }
+}
+
+impl<T: Clone + PartialEq> Cells for SparseGrid<T> {
+    type Cell = T;
+
+    fn cell(&self, p: Point) -> Option<&T> {
+        Some(self.get(p))
+    }
+}
+
+/// Renders the rectangle given by [`SparseGrid::bounds`], and nothing when it is empty.
+impl<T: Clone + PartialEq + fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((min, max)) = self.bounds() {
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0 {
+                    write!(f, "{}", self.get((x, y)))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Grid<char>, ParseError> {
+        Grid::parse(s.as_bytes(), |ch| match ch {
+            '.' | '#' | 'L' => Ok(ch),
+            _ => Err(ParseError::new(format!("unrecognizable char {:?}", ch))),
+        })
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = parse("#..\n.#L \n\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'L');
+        assert_eq!(grid.get((3, 1)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.to_string(), "#..\n.#L\n");
+
+        let e = parse("#..\n.x.\n").err().unwrap();
+        assert_eq!((e.line(), e.column()), (Some(2), Some(2)));
+        let e = parse("#..\n.#\n").err().unwrap();
+        assert_eq!(e.message(), "expected 3 cells, found 2");
+        assert_eq!(parse("").unwrap().to_string(), "");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::from_fn(3, 3, |(x, y)| x + 3 * y);
+        let four: Vec<_> = grid
+            .neighbours((0, 0), Neighbourhood::Four)
+            .map(|(_, &n)| n)
+            .collect();
+        assert_eq!(four, vec![1, 3]);
+        assert_eq!(grid.neighbours((0, 0), Neighbourhood::Eight).count(), 3);
+        assert_eq!(grid.neighbours((1, 1), Neighbourhood::Eight).count(), 8);
+        assert_eq!(
+            grid.toroidal()
+                .neighbours((0, 0), Neighbourhood::Four)
+                .map(|(_, &n)| n)
+                .collect::<Vec<_>>(),
+            vec![6, 2, 1, 3]
+        );
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = Grid::from_fn(4, 3, |(x, y)| x + 4 * y);
+        let ray: Vec<_> = grid.ray((0, 0), (1, 1)).collect();
+        assert_eq!(ray, vec![((1, 1), &5), ((2, 2), &10)]);
+        assert_eq!(grid.ray((3, 0), (1, 0)).count(), 0);
+        let ray: Vec<_> = grid
+            .toroidal()
+            .ray((0, 0), (3, 1))
+            .map(|(p, &n)| (p, n))
+            .take(3)
+            .collect();
+        assert_eq!(ray, vec![((3, 1), 7), ((6, 2), 10), ((9, 3), 1)]);
+    }
+
+    #[test]
+    fn test_sparse() {
+        let grid = parse(".#.\n..#\n###\n").unwrap();
+        let mut sparse = SparseGrid::from_grid(&grid, '.');
+        assert_eq!(sparse.len(), 5);
+        assert_eq!(*sparse.get((-10, 10)), '.');
+        assert_eq!(sparse.neighbours((1, 1), Neighbourhood::Eight).count(), 8);
+        sparse.set((-1, 0), '#');
+        sparse.set((2, 2), '.');
+        assert_eq!(sparse.bounds(), Some(((-1, 0), (2, 2))));
+        assert_eq!(sparse.to_string(), "#.#.\n...#\n.##.\n");
+        assert_eq!(SparseGrid::new('.').to_string(), "");
+    }
+}
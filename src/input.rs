@@ -0,0 +1,124 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Where a day reads its puzzle input from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum InputSource {
+    /// The day's own input file, `input/dayNN`.
+    #[default]
+    Default,
+    /// An explicit file.
+    File(PathBuf),
+    Stdin,
+    /// A named input set, `input/<profile>/dayNN`.
+    Profile(String),
+}
+
+impl InputSource {
+    /// Returns the file to read for a day whose default input file is `default`, or
+    /// `None` when reading from stdin.
+    pub fn path(&self, default: &str) -> Option<PathBuf> {
+        match self {
+            Self::Default => Some(PathBuf::from(default)),
+            Self::File(path) => Some(path.clone()),
+            Self::Stdin => None,
+            Self::Profile(profile) => {
+                let default = Path::new(default);
+                let dir = default.parent().unwrap_or_else(|| Path::new(""));
+                Some(dir.join(profile).join(default.file_name()?))
+            }
+        }
+    }
+
+    pub fn open(&self, default: &str) -> io::Result<Box<dyn Read>> {
+        match self.path(default) {
+            Some(path) => match File::open(&path) {
+                Ok(f) => Ok(Box::new(f)),
+                Err(e) => Err(io::Error::new(
+                    e.kind(),
+                    format!("{}: {}", path.display(), e),
+                )),
+            },
+            None => Ok(Box::new(io::stdin())),
+        }
+    }
+
+    pub fn read(&self, default: &str) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.open(default)?.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Whether the source can serve more than one day.
+    pub fn is_per_day(&self) -> bool {
+        matches!(self, Self::Default | Self::Profile(_))
+    }
+}
+
+/// Reads the non-blank lines of the file at `p`, parsing each with `parse_line`. A missing
+/// file has no lines, and a line `parse_line` rejects is reported as an invalid `what`.
+pub fn load_lines<T, P: AsRef<Path>>(
+    p: P,
+    what: &str,
+    mut parse_line: impl FnMut(&str) -> Option<T>,
+) -> io::Result<Vec<T>> {
+    let content = match fs::read_to_string(p) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            parse_line(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid {} {:?}", what, line),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let default = "input/day07";
+        assert_eq!(
+            InputSource::Default.path(default),
+            Some(PathBuf::from("input/day07"))
+        );
+        assert_eq!(
+            InputSource::File(PathBuf::from("/tmp/example")).path(default),
+            Some(PathBuf::from("/tmp/example"))
+        );
+        assert_eq!(InputSource::Stdin.path(default), None);
+        assert_eq!(
+            InputSource::Profile("alice".to_string()).path(default),
+            Some(PathBuf::from("input/alice/day07"))
+        );
+    }
+
+    #[test]
+    fn test_load_lines() {
+        let path = std::env::temp_dir().join(format!("aoc2020_load_lines_{}", std::process::id()));
+        let parse = |line: &str| line.parse::<i32>().ok();
+        assert_eq!(load_lines(&path, "number", parse).unwrap(), vec![]);
+
+        fs::write(&path, "1\n\n  \n2\n").unwrap();
+        let numbers = load_lines(&path, "number", parse);
+        fs::write(&path, "1\nx\n").unwrap();
+        let invalid = load_lines(&path, "number", parse);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(numbers.unwrap(), vec![1, 2]);
+        let e = invalid.err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), "invalid number \"x\"");
+    }
+}
@@ -0,0 +1,51 @@
+//! Advent of Code 2020 solutions.
+//!
+//! Each `dayNN` module exposes its `Problem`, which implements [`solver::Solver`], and the
+//! types its input is parsed into. [`registry()`] collects every day as a
+//! [`registry::DynSolver`].
+
+pub mod alloc;
+pub mod answer;
+pub mod automaton;
+pub mod bench;
+pub mod client;
+pub mod error;
+pub mod example;
+#[cfg(test)]
+mod fuzz;
+pub mod grid;
+pub mod input;
+pub mod numtheory;
+pub mod output;
+pub mod random;
+#[macro_use]
+pub mod registry;
+pub mod runner;
+#[macro_use]
+pub mod solver;
+pub mod submit;
+#[macro_use]
+pub mod trace;
+pub mod verify;
+pub mod watch;
+
+// After the modules whose macros the days use.
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+
+// The `register_days!` call for the modules above, generated by build.rs.
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
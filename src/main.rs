@@ -1,51 +1,576 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod solver;
-
-use crate::solver::Solver;
-
-fn solve_day(day: i32) {
-    match day {
-        // TODO: Write macro to automate solve by day
-        1 => day01::Problem {}.solve(),
-        2 => day02::Problem {}.solve(),
-        3 => day03::Problem {}.solve(),
-        4 => day04::Problem {}.solve(),
-        5 => day05::Problem {}.solve(),
-        6 => day06::Problem {}.solve(),
-        7 => day07::Problem {}.solve(),
-        8 => day08::Problem {}.solve(),
-        9 => day09::Problem {}.solve(),
-        10 => day10::Problem {}.solve(),
-        11 => day11::Problem {}.solve(),
-        12 => day12::Problem {}.solve(),
-        13 => day13::Problem {}.solve(),
-        14 => day14::Problem {}.solve(),
-        15 => day15::Problem {}.solve(),
-        16 => day16::Problem {}.solve(),
-        d => println!("Day {} hasn't been solved yet :(", d),
+mod cli;
+mod scaffold;
+
+use crate::cli::Args;
+use adventofcode2020::alloc::Bytes;
+use adventofcode2020::bench::{self, Baseline, BenchOptions};
+use adventofcode2020::client::{self, Client, Config};
+use adventofcode2020::error::Error;
+use adventofcode2020::example::Example;
+use adventofcode2020::input::InputSource;
+use adventofcode2020::output::{self, Format};
+use adventofcode2020::registry;
+use adventofcode2020::registry::{DynSolver, Registry};
+use adventofcode2020::runner::{self, Outcome, Report};
+use adventofcode2020::solver::{timed, Overrides, Part};
+use adventofcode2020::submit::{self, Response, Submissions};
+use adventofcode2020::trace::{self, Event};
+use adventofcode2020::verify::{self, Answers, Verdict};
+use adventofcode2020::watch::Watch;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const USAGE: &str = "Usage:
+    adventofcode2020 <day> [INPUT] [PARAMS] [--part 1|2] [--format text|json|csv] [--explain]
+    adventofcode2020 all [--profile NAME] [--part 1|2] [--format text|json|csv]
+    adventofcode2020 watch <day> [INPUT] [PARAMS] [--example PATH] [--interval MS]
+    adventofcode2020 list
+    adventofcode2020 new <day>
+    adventofcode2020 fetch <day>
+    adventofcode2020 submit <day> <1|2>
+    adventofcode2020 bench <day|all> [INPUT] [PARAMS] [--iterations N] [--warmup N] [--threshold PCT] [--save]
+    adventofcode2020 verify [day|all] [INPUT] [--record]
+
+INPUT selects where puzzle inputs are read from, instead of input/dayNN:
+    --input PATH     read the given file
+    --stdin          read standard input
+    --profile NAME   read input/NAME/dayNN
+
+PARAMS change a single day's puzzle constants, listed by `list`:
+    --param NAME=VALUE   may be given several times";
+
+const INPUT_OPTIONS: [&str; 3] = ["input", "stdin", "profile"];
+const OUTPUT_OPTIONS: [&str; 2] = ["part", "format"];
+
+fn input_source(args: &Args) -> Result<InputSource, String> {
+    let path: Option<PathBuf> = args.value("input")?;
+    let profile: Option<String> = args.value("profile")?;
+    match (path, profile, args.flag("stdin")) {
+        (None, None, false) => Ok(InputSource::Default),
+        (Some(path), None, false) => Ok(InputSource::File(path)),
+        (None, Some(profile), false) => Ok(InputSource::Profile(profile)),
+        (None, None, true) => Ok(InputSource::Stdin),
+        _ => Err("Only one of --input, --stdin and --profile can be given".to_string()),
+    }
+}
+
+/// Rejects options outside `known` and the input options, and returns the input source.
+fn check_args(args: &Args, known: &[&str]) -> Result<InputSource, String> {
+    let known: Vec<_> = known.iter().chain(INPUT_OPTIONS.iter()).copied().collect();
+    args.check_options(&known)?;
+    input_source(args)
+}
+
+/// Returns the parts selected with `--part`, both by default.
+fn selected_parts(args: &Args) -> Result<Vec<Part>, String> {
+    Ok(match args.value("part")? {
+        Some(part) => vec![part],
+        None => Part::BOTH.to_vec(),
+    })
+}
+
+/// Returns the day parameters given with `--param NAME=VALUE`.
+fn param_overrides(args: &Args) -> Result<Overrides, String> {
+    args.values("param")?
+        .into_iter()
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) => Ok((name.to_string(), value.to_string())),
+            None => Err(format!(
+                "Invalid value for --param: {}, expected NAME=VALUE",
+                param
+            )),
+        })
+        .collect()
+}
+
+fn check_per_day(source: &InputSource, days: usize) -> Result<(), String> {
+    if days > 1 && !source.is_per_day() {
+        Err("--input and --stdin can only be used with a single day".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn select_days<'a>(
+    registry: &'a Registry,
+    arg: Option<&str>,
+) -> Result<Vec<&'a dyn DynSolver>, String> {
+    match arg {
+        Some("all") => Ok(registry.days().collect()),
+        Some(arg) => {
+            let day = arg.parse().map_err(|_| format!("Invalid day: {}", arg))?;
+            let entry = registry
+                .get(day)
+                .ok_or_else(|| format!("Day {} hasn't been solved yet :(", day))?;
+            Ok(vec![entry])
+        }
+        None => Err("Missing day".to_string()),
+    }
+}
+
+/// Prints `reports` as JSON or CSV records, failing if any day failed.
+fn print_records(reports: &[Report], format: Format) -> Result<(), String> {
+    let records = output::records(reports);
+    match format {
+        Format::Json => println!("{}", output::to_json(&records)),
+        _ => print!("{}", output::to_csv(&records)),
+    }
+    let failures = reports
+        .iter()
+        .filter(|report| matches!(report.outcome, Outcome::Failed(_)))
+        .count();
+    if failures > 0 {
+        Err(format!("{} day(s) failed", failures))
+    } else {
+        Ok(())
+    }
+}
+
+/// Prints the events recorded with `--explain`, indented under what they explain.
+fn print_events(events: &[Event]) {
+    for event in events {
+        println!("  {}", event);
+    }
+}
+
+fn solve_day(args: &Args, day: i32) -> Result<(), String> {
+    let source = check_args(args, &["part", "format", "param", "explain"])?;
+    let parts = selected_parts(args)?;
+    let overrides = param_overrides(args)?;
+    let format = args.value("format")?.unwrap_or(Format::Text);
+    if args.flag("explain") && format != Format::Text {
+        return Err("--explain can only be used with --format text".to_string());
+    }
+    let registry = registry();
+    let report = match (registry.get(day), format) {
+        (Some(entry), Format::Text) => {
+            let run = || entry.run(&source, &parts, &overrides);
+            let solution = if args.flag("explain") {
+                trace::explain(run)
+            } else {
+                run()
+            }
+            .map_err(|e| format!("error: {}", e))?;
+            if !solution.parse_events.is_empty() {
+                println!("Parsed:");
+                print_events(&solution.parse_events);
+            }
+            for solution in solution.parts {
+                println!("Solution {}: {}", solution.part, solution.answer);
+                print_events(&solution.events);
+            }
+            return Ok(());
+        }
+        (None, Format::Text) => {
+            println!("Day {} hasn't been solved yet :(", day);
+            return Ok(());
+        }
+        (Some(entry), _) => {
+            runner::quiet_panics(|| runner::run_day(entry, &source, &parts, &overrides))
+        }
+        (None, _) => Report {
+            day,
+            outcome: Outcome::Failed("not solved yet".to_string()),
+        },
+    };
+    print_records(&[report], format)
+}
+
+fn solve_all(args: &Args) -> Result<(), String> {
+    let source = check_args(args, &OUTPUT_OPTIONS)?;
+    let parts = selected_parts(args)?;
+    let format = args.value("format")?.unwrap_or(Format::Text);
+    let registry = registry();
+    check_per_day(&source, registry.days().count())?;
+    let reports = runner::run_all(&registry, &source, &parts);
+    match format {
+        Format::Text => {
+            runner::print_table(&reports);
+            Ok(())
+        }
+        _ => print_records(&reports, format),
+    }
+}
+
+/// Solves both parts of the watched input and prints the answers with timings.
+fn print_watched_input(
+    entry: &dyn DynSolver,
+    source: &InputSource,
+    overrides: &Overrides,
+    path: &Path,
+) {
+    match runner::guard(|| entry.run(source, &Part::BOTH, overrides)) {
+        Ok(solution) => {
+            println!("{}: parsed in {:.2?}", path.display(), solution.parse_time);
+            for solution in solution.parts {
+                println!(
+                    "  part {}: {} ({:.2?})",
+                    solution.part, solution.answer, solution.time
+                );
+            }
+        }
+        Err(e) => println!("{}: error: {}", path.display(), e),
+    }
+}
+
+/// Solves the watched example and prints its answers against the expected ones.
+fn print_watched_example(entry: &dyn DynSolver, path: &Path) {
+    let result = runner::guard(|| {
+        let example = Example::load(path)?;
+        let parts: Vec<_> = example.expected.iter().map(|(part, _)| *part).collect();
+        let (answers, time) =
+            timed(|| entry.solve_example(&mut example.input.as_bytes(), &parts, &example.params));
+        let answers = answers.map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok::<_, String>((example, answers, time))
+    });
+    match result {
+        Ok((example, answers, time)) => {
+            println!("{}: solved in {:.2?}", path.display(), time);
+            for ((part, expected), answer) in example.expected.iter().zip(answers) {
+                if *expected == answer.to_string() {
+                    println!("  part {}: {} ok", part, answer);
+                } else {
+                    println!("  part {}: {}, expected {}", part, answer, expected);
+                }
+            }
+        }
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+/// Re-solves a day whenever its input or example file changes. Errors are printed and
+/// watching goes on.
+fn watch(args: &Args) -> Result<(), String> {
+    let source = check_args(args, &["example", "interval", "param"])?;
+    if source == InputSource::Stdin {
+        return Err("watch needs an input file to poll, not --stdin".to_string());
+    }
+    let overrides = param_overrides(args)?;
+    let example: Option<PathBuf> = args.value("example")?;
+    let interval = Duration::from_millis(args.value("interval")?.unwrap_or(500));
+    let registry = registry();
+    let arg = args.positional(1).ok_or("Missing day")?;
+    let day = arg.parse().map_err(|_| format!("Invalid day: {}", arg))?;
+    let entry = registry
+        .get(day)
+        .ok_or_else(|| format!("Day {} hasn't been solved yet :(", day))?;
+
+    let input = source.path(&entry.input_file()).unwrap();
+    let mut files = vec![input.clone()];
+    files.extend(example.clone());
+    let mut watch = Watch::new(files);
+    println!("Watching day {}, press Ctrl-C to stop", day);
+    loop {
+        if watch.poll() {
+            println!();
+            runner::quiet_panics(|| {
+                print_watched_input(entry, &source, &overrides, &input);
+                if let Some(example) = &example {
+                    print_watched_example(entry, example);
+                }
+            });
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn list_days() -> Result<(), String> {
+    let registry = registry();
+    let days: Vec<_> = registry
+        .days()
+        .map(|entry| entry.day().to_string())
+        .collect();
+    println!("Solved days: {}", days.join(", "));
+    println!("Parameters:");
+    for entry in registry.days() {
+        for (name, value) in entry.params() {
+            println!("  day {:>2}  {} = {}", entry.day(), name, value);
+        }
+    }
+    Ok(())
+}
+
+fn new_day(args: &Args) -> Result<(), String> {
+    args.check_options(&[])?;
+    let arg = args.positional(1).ok_or("Missing day")?;
+    let day = arg.parse().map_err(|_| format!("Invalid day: {}", arg))?;
+    for path in scaffold::new_day(Path::new("."), day)? {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn load_config() -> Result<Config, String> {
+    Ok(Config::load(client::CONFIG_FILE)?.with_env(|name| std::env::var(name).ok()))
+}
+
+fn fetch(args: &Args) -> Result<(), String> {
+    args.check_options(&[])?;
+    let arg = args.positional(1).ok_or("Missing day")?;
+    let day = arg.parse().map_err(|_| format!("Invalid day: {}", arg))?;
+    let (path, downloaded) = client::fetch(
+        &Client::new(load_config()?),
+        Path::new("input"),
+        day,
+        SystemTime::now(),
+    )?;
+    if downloaded {
+        println!("Wrote {}", path.display());
+    } else {
+        println!("{} is already cached", path.display());
+    }
+    Ok(())
+}
+
+fn submit(args: &Args) -> Result<(), String> {
+    args.check_options(&[])?;
+    let registry = registry();
+    let arg = args.positional(1).ok_or("Missing day")?;
+    let day = arg.parse().map_err(|_| format!("Invalid day: {}", arg))?;
+    let entry = registry
+        .get(day)
+        .ok_or_else(|| format!("Day {} hasn't been solved yet :(", day))?;
+    let part: Part = match args.positional(2) {
+        Some(arg) => arg.parse().map_err(|_| format!("Invalid part: {}", arg))?,
+        None => return Err("Missing part".to_string()),
+    };
+    let solution = runner::quiet_panics(|| {
+        runner::guard(|| {
+            entry
+                .run(&InputSource::Default, &[part], &Overrides::new())
+                .map_err(|e| e.to_string())
+        })
+    })
+    .map_err(|e| format!("error: {}", e))?;
+    let answer = &solution.parts[0].answer;
+    if !answer.is_value() {
+        return Err(format!(
+            "Day {} part {} has nothing to submit: {}",
+            entry.day(),
+            part,
+            answer
+        ));
+    }
+    let answer = &answer.to_string();
+
+    let mut submissions = Submissions::load(submit::SUBMISSIONS_FILE)
+        .map_err(|e| format!("Unable to read {}: {}", submit::SUBMISSIONS_FILE, e))?;
+    submissions.check(entry.day(), part, answer)?;
+    let page = Client::new(load_config()?).post_answer(entry.day(), part, answer)?;
+    let response = Response::parse(&page);
+    submissions.record(entry.day(), part, answer, response.clone());
+    submissions
+        .save(submit::SUBMISSIONS_FILE)
+        .map_err(|e| format!("Unable to write {}: {}", submit::SUBMISSIONS_FILE, e))?;
+
+    let message = format!(
+        "Day {} part {}: {}: {}",
+        entry.day(),
+        part,
+        answer,
+        response
+    );
+    if response == Response::Right {
+        println!("{}", message);
+        Ok(())
+    } else {
+        Err(message)
+    }
+}
+
+fn bench(args: &Args) -> Result<(), String> {
+    let source = check_args(
+        args,
+        &["iterations", "warmup", "threshold", "save", "param"],
+    )?;
+    let overrides = param_overrides(args)?;
+    let defaults = BenchOptions::default();
+    let options = BenchOptions {
+        iterations: args.value("iterations")?.unwrap_or(defaults.iterations),
+        warmup: args.value("warmup")?.unwrap_or(defaults.warmup),
+    };
+    let threshold = args.value::<f64>("threshold")?.unwrap_or(10.0) / 100.0;
+
+    let registry = registry();
+    let days = select_days(&registry, args.positional(1))?;
+    check_per_day(&source, days.len())?;
+    if days.len() > 1 && !overrides.is_empty() {
+        return Err("--param can only be used with a single day".to_string());
+    }
+    let mut baseline = Baseline::load(bench::BASELINE_FILE)
+        .map_err(|e| format!("Unable to read {}: {}", bench::BASELINE_FILE, e))?;
+    let mut regressions = 0;
+
+    for entry in days {
+        let result =
+            runner::quiet_panics(|| runner::guard(|| entry.bench(&options, &source, &overrides)));
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                println!("Day {}: error: {}", entry.day(), e);
+                continue;
+            }
+        };
+
+        println!(
+            "Day {} ({} iterations, {} warmup)",
+            result.day, options.iterations, options.warmup
+        );
+        for (phase, stats) in result.phases.iter() {
+            let flag = match baseline.regression(result.day, *phase, stats.median, threshold) {
+                Some(change) => {
+                    regressions += 1;
+                    format!("  REGRESSION +{:.1}%", change * 100.0)
+                }
+                None => String::new(),
+            };
+            let allocs = match result.allocs.iter().find(|(p, _)| p == phase) {
+                Some((_, alloc)) => format!(
+                    "  allocs {:>9}  bytes {:>10}  peak {:>10}",
+                    alloc.allocations,
+                    Bytes(alloc.bytes),
+                    Bytes(alloc.peak)
+                ),
+                None => String::new(),
+            };
+            println!(
+                "  {:<6}  min {:>10.2?}  median {:>10.2?}  max {:>10.2?}{}{}",
+                phase, stats.min, stats.median, stats.max, allocs, flag
+            );
+        }
+        if args.flag("save") {
+            baseline.record(&result);
+        }
+    }
+
+    if args.flag("save") {
+        baseline
+            .save(bench::BASELINE_FILE)
+            .map_err(|e| format!("Unable to write {}: {}", bench::BASELINE_FILE, e))?;
+        println!("Saved baseline to {}", bench::BASELINE_FILE);
+    }
+    if regressions > 0 {
+        Err(format!(
+            "{} phase(s) regressed past the threshold",
+            regressions
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn verify(args: &Args) -> Result<(), String> {
+    let source = check_args(args, &["record"])?;
+    let registry = registry();
+    let days = select_days(&registry, Some(args.positional(1).unwrap_or("all")))?;
+    check_per_day(&source, days.len())?;
+    if source == InputSource::Stdin {
+        return Err("verify needs an input file to hash, not --stdin".to_string());
+    }
+    let mut answers = Answers::load(verify::ANSWERS_FILE)
+        .map_err(|e| format!("Unable to read {}: {}", verify::ANSWERS_FILE, e))?;
+    let mut failures = 0;
+
+    for entry in days {
+        let result = runner::quiet_panics(|| {
+            runner::guard(|| -> Result<_, Error> {
+                let raw_input = source.read(&entry.input_file())?;
+                let answers = entry.solve_from_reader(&mut &raw_input[..])?;
+                Ok((verify::input_hash(&raw_input), answers))
+            })
+        });
+        let (input_hash, (first, second)) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                failures += 1;
+                println!("Day {:>2}: error: {}", entry.day(), e);
+                continue;
+            }
+        };
+
+        for (part, answer) in [(Part::One, &first), (Part::Two, &second)].iter() {
+            let part = part.number();
+            let label = format!("Day {:>2} part {}", entry.day(), part);
+            if args.flag("record") {
+                if answer.is_value() {
+                    answers.record(entry.day(), part, input_hash, &answer.to_string());
+                    println!("{}: recorded {}", label, answer);
+                } else {
+                    println!("{}: not recorded, {}", label, answer);
+                }
+                continue;
+            }
+            match answers.check(entry.day(), part, input_hash, answer) {
+                Verdict::Correct => println!("{}: ok {}", label, answer),
+                Verdict::Unknown => println!("{}: unknown {}", label, answer),
+                Verdict::OtherInput => println!(
+                    "{}: unknown {} (stored answer is for a different input)",
+                    label, answer
+                ),
+                Verdict::NoSolution => println!("{}: no solution", label),
+                Verdict::NotImplemented => println!("{}: not implemented", label),
+                Verdict::Wrong { expected } => {
+                    failures += 1;
+                    println!("{}: MISMATCH", label);
+                    println!("  - expected: {}", expected);
+                    println!("  + actual:   {}", answer);
+                }
+            }
+        }
+    }
+
+    if args.flag("record") {
+        answers
+            .save(verify::ANSWERS_FILE)
+            .map_err(|e| format!("Unable to write {}: {}", verify::ANSWERS_FILE, e))?;
+        println!("Saved answers to {}", verify::ANSWERS_FILE);
+    }
+    if failures > 0 {
+        Err(format!("{} check(s) failed", failures))
+    } else {
+        Ok(())
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    match args.positional(0) {
+        Some("bench") => bench(args),
+        Some("verify") => verify(args),
+        Some("all") => solve_all(args),
+        Some("watch") => watch(args),
+        Some("new") => new_day(args),
+        Some("fetch") => fetch(args),
+        Some("submit") => submit(args),
+        Some("list") => args.check_options(&[]).and_then(|_| list_days()),
+        Some(arg) => match arg.parse() {
+            Ok(day) => solve_day(args, day),
+            Err(_) => Err(format!("Invalid day: {}\n{}", arg, USAGE)),
+        },
+        None => Err(USAGE.to_string()),
     }
 }
 
 fn main() {
-    let day = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| String::from("1"))
-        .parse()
-        .unwrap_or(1);
-    solve_day(day);
+    let result = Args::parse(
+        std::env::args().skip(1),
+        &[
+            "iterations",
+            "warmup",
+            "threshold",
+            "input",
+            "profile",
+            "part",
+            "format",
+            "example",
+            "interval",
+            "param",
+        ],
+    )
+    .and_then(|args| run(&args));
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,161 @@
+//! Modular arithmetic on i128, for puzzles whose answers are solutions of congruences.
+//!
+//! Products are reduced without overflowing, so any modulus up to `i128::MAX` works.
+
+/// Returns `(g, x, y)` with `a * x + b * y == g`, where `g` is the non-negative greatest
+/// common divisor of `a` and `b`.
+///
+/// Panics if either number is `i128::MIN`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    extended_gcd(a, b).0
+}
+
+/// Returns `a * b` modulo `m`, in `0..m`.
+///
+/// Panics if `m` is not positive.
+pub fn mulmod(a: i128, b: i128, m: i128) -> i128 {
+    assert!(m > 0, "modulus {} is not positive", m);
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    // Both are below m < 2^127, so doubling them and adding two of them fits in a u128.
+    let (mut a, mut b, m) = (a as u128, b as u128, m as u128);
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = (result + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    result as i128
+}
+
+/// Returns `base` to the power `exp` modulo `m`, in `0..m`.
+///
+/// Panics if `m` is not positive.
+pub fn modpow(base: i128, mut exp: u128, m: i128) -> i128 {
+    let mut base = mulmod(base, 1, m);
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mulmod(result, base, m);
+        }
+        base = mulmod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Returns the `x` in `0..m` with `a * x` congruent to 1 modulo `m`, or None if `a` and `m`
+/// are not coprime.
+///
+/// Panics if `m` is not positive.
+pub fn modinv(a: i128, m: i128) -> Option<i128> {
+    assert!(m > 0, "modulus {} is not positive", m);
+    match extended_gcd(a.rem_euclid(m), m) {
+        (1, x, _) => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+/// Solves the system of `x` congruent to `residue` modulo `modulus` for every
+/// `(residue, modulus)` pair, which need not be coprime.
+///
+/// Returns the smallest non-negative `x` and the least common multiple of the moduli, which
+/// every other solution differs from `x` by a multiple of. Returns None if the congruences
+/// contradict each other or that multiple does not fit in an i128.
+///
+/// Panics if a modulus is not positive.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |(x, m), &(residue, modulus)| {
+            assert!(modulus > 0, "modulus {} is not positive", modulus);
+            // x + m * k must equal residue modulo modulus; solve for k using p * m = g.
+            let (g, p, _) = extended_gcd(m, modulus);
+            let diff = residue.rem_euclid(modulus) - x;
+            if diff % g != 0 {
+                return None;
+            }
+            let reduced = modulus / g;
+            let k = mulmod(diff / g, p, reduced);
+            let lcm = m.checked_mul(reduced)?;
+            // x < m and k < reduced, so the sum stays below lcm.
+            Some((x + m * k, lcm))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_gcd() {
+        for &(a, b, g) in [
+            (240, 46, 2),
+            (-240, 46, 2),
+            (0, 7, 7),
+            (0, 0, 0),
+            (17, -5, 1),
+        ]
+        .iter()
+        {
+            let (d, x, y) = extended_gcd(a, b);
+            assert_eq!(d, g, "gcd({}, {})", a, b);
+            assert_eq!(a * x + b * y, d, "gcd({}, {})", a, b);
+        }
+        assert_eq!(gcd(i128::MAX, i128::MAX - 1), 1);
+    }
+
+    #[test]
+    fn test_mulmod() {
+        let m = i128::MAX;
+        assert_eq!(mulmod(m - 1, m - 1, m), 1);
+        assert_eq!(mulmod(-3, 5, 7), 6);
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(5, 0, 1), 0);
+        // Fermat: a^(p-1) = 1 modulo the prime 2^127 - 1.
+        assert_eq!(modpow(3, (m - 1) as u128, m), 1);
+    }
+
+    #[test]
+    fn test_modinv() {
+        assert_eq!(modinv(3, 11), Some(4));
+        assert_eq!(modinv(-3, 11), Some(7));
+        assert_eq!(modinv(6, 9), None);
+        let m = i128::MAX;
+        assert_eq!(mulmod(modinv(12345, m).unwrap(), 12345, m), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Moduli that share a factor, consistent and not.
+        assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[(3, 4), (4, 6)]), None);
+        assert_eq!(crt(&[(-1, 5), (9, 5)]), Some((4, 5)));
+        let big = (1 << 100) + 277;
+        assert_eq!(crt(&[(1, big), (0, 2)]), Some((big + 1, 2 * big)));
+        assert_eq!(crt(&[(0, i128::MAX), (0, 2)]), None);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::answer::Answer;
+use crate::runner::{Outcome, Report};
+use crate::solver::Part;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("invalid format {:?}", s)),
+        }
+    }
+}
+
+/// One row of structured output: either a solved part or an error for the whole day.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Record<'a> {
+    pub day: i32,
+    pub part: Option<Part>,
+    pub answer: Option<&'a Answer>,
+    pub duration: Option<Duration>,
+    pub error: Option<&'a str>,
+}
+
+impl Record<'_> {
+    /// `solved` for a part with an answer, `no_solution` or `not_implemented` for a part
+    /// without one, and `error` for a day that failed.
+    pub fn status(&self) -> &'static str {
+        match self.answer {
+            Some(Answer::NoSolution) => "no_solution",
+            Some(Answer::NotImplemented) => "not_implemented",
+            Some(_) => "solved",
+            None => "error",
+        }
+    }
+
+    /// The answer when there is one.
+    fn value(&self) -> Option<&Answer> {
+        self.answer.filter(|answer| answer.is_value())
+    }
+}
+
+pub fn records(reports: &[Report]) -> Vec<Record<'_>> {
+    reports
+        .iter()
+        .flat_map(|report| match &report.outcome {
+            Outcome::Solved(solution) => solution
+                .parts
+                .iter()
+                .map(|solution| Record {
+                    day: report.day,
+                    part: Some(solution.part),
+                    answer: Some(&solution.answer),
+                    duration: Some(solution.time),
+                    error: None,
+                })
+                .collect(),
+            Outcome::Failed(message) => vec![Record {
+                day: report.day,
+                part: None,
+                answer: None,
+                duration: None,
+                error: Some(message),
+            }],
+        })
+        .collect()
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if ch.is_control() => write!(result, "\\u{:04x}", ch as u32).unwrap(),
+            ch => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_or_null<T, F: FnOnce(T) -> String>(value: Option<T>, f: F) -> String {
+    value.map_or_else(|| "null".to_string(), f)
+}
+
+/// Integers that fit in an i128 become JSON numbers; bigger ones and text become strings.
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Int(n) => n.to_string(),
+        answer => json_string(&answer.to_string()),
+    }
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    let rows: Vec<_> = records
+        .iter()
+        .map(|record| {
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"status\": {}, \"answer\": {}, \"duration_ns\": {}, \"error\": {}}}",
+                record.day,
+                json_or_null(record.part, |part| part.to_string()),
+                json_string(record.status()),
+                json_or_null(record.value(), json_answer),
+                json_or_null(record.duration, |d| d.as_nanos().to_string()),
+                json_or_null(record.error, json_string),
+            )
+        })
+        .collect();
+    if rows.is_empty() {
+        "[]".to_string()
+    } else {
+        format!("[\n{}\n]", rows.join(",\n"))
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn to_csv(records: &[Record]) -> String {
+    let mut result = String::from("day,part,status,answer,duration_ns,error\n");
+    for record in records {
+        writeln!(
+            result,
+            "{},{},{},{},{},{}",
+            record.day,
+            record.part.map(|part| part.to_string()).unwrap_or_default(),
+            record.status(),
+            record
+                .value()
+                .map(|answer| csv_field(&answer.to_string()))
+                .unwrap_or_default(),
+            record
+                .duration
+                .map(|d| d.as_nanos().to_string())
+                .unwrap_or_default(),
+            record.error.map(csv_field).unwrap_or_default(),
+        )
+        .unwrap();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{PartSolution, Solution};
+
+    fn solution(parts: Vec<(Part, Answer)>) -> Outcome {
+        Outcome::Solved(Solution {
+            parse_time: Duration::from_nanos(700),
+            parse_alloc: None,
+            parse_events: Vec::new(),
+            parts: parts
+                .into_iter()
+                .map(|(part, answer)| PartSolution {
+                    part,
+                    answer,
+                    time: Duration::from_nanos(1500),
+                    alloc: None,
+                    events: Vec::new(),
+                })
+                .collect(),
+        })
+    }
+
+    fn reports() -> Vec<Report> {
+        vec![
+            Report {
+                day: 1,
+                outcome: solution(vec![
+                    (Part::One, Answer::Int(42)),
+                    (Part::Two, Answer::NoSolution),
+                ]),
+            },
+            Report {
+                day: 11,
+                outcome: solution(vec![(Part::Two, Answer::NotImplemented)]),
+            },
+            Report {
+                day: 16,
+                outcome: Outcome::Failed("panicked: \"bad\"".to_string()),
+            },
+            Report {
+                day: 21,
+                outcome: solution(vec![(Part::Two, Answer::Text("a,b".to_string()))]),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_json() {
+        let reports = reports();
+        assert_eq!(
+            to_json(&records(&reports)),
+            r#"[
+  {"day": 1, "part": 1, "status": "solved", "answer": 42, "duration_ns": 1500, "error": null},
+  {"day": 1, "part": 2, "status": "no_solution", "answer": null, "duration_ns": 1500, "error": null},
+  {"day": 11, "part": 2, "status": "not_implemented", "answer": null, "duration_ns": 1500, "error": null},
+  {"day": 16, "part": null, "status": "error", "answer": null, "duration_ns": null, "error": "panicked: \"bad\""},
+  {"day": 21, "part": 2, "status": "solved", "answer": "a,b", "duration_ns": 1500, "error": null}
+]"#
+        );
+        assert_eq!(to_json(&[]), "[]");
+    }
+
+    #[test]
+    fn test_csv() {
+        let reports = reports();
+        assert_eq!(
+            to_csv(&records(&reports)),
+            "day,part,status,answer,duration_ns,error
+1,1,solved,42,1500,
+1,2,no_solution,,1500,
+11,2,not_implemented,,1500,
+16,,error,,,\"panicked: \"\"bad\"\"\"
+21,2,solved,\"a,b\",1500,
+"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+/// Small seeded pseudo-random generator (xorshift64*) for generating test inputs.
+///
+/// Not suitable for anything but tests: the point is that a failing seed can be replayed.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Mix the seed so that small consecutive seeds give unrelated streams; the state
+        // must never be zero.
+        let state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ 0x2545_f491_4f6c_dd1d;
+        Self {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number in `0..n`; `n` must not be zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Returns a number in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        range.start + self.below((range.end - range.start) as u64) as i64
+    }
+
+    /// Returns true with probability `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        let first: Vec<_> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first[0], Rng::new(8).next_u64());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(0);
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            let n = rng.range(-2..3);
+            assert!((-2..3).contains(&n));
+            seen[(n + 2) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        let mut items: Vec<_> = (0..20).collect();
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
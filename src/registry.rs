@@ -0,0 +1,215 @@
+use crate::answer::Answer;
+use crate::bench::{self, BenchOptions, DayBench};
+use crate::error::Error;
+use crate::input::InputSource;
+use crate::solver::{DayParams, Overrides, Part, Solution, Solver};
+use std::io::Read;
+use std::panic::RefUnwindSafe;
+
+/// Builds the `registry()` that maps day numbers to the `dayNN` modules.
+///
+/// The modules are declared in lib.rs like any other, so that rustfmt formats them, and
+/// build.rs generates the call from those declarations: adding a day takes only its
+/// `pub mod` line.
+macro_rules! register_days {
+    ($($module:ident),* $(,)?) => {
+        /// Every solved day, in order.
+        pub fn registry() -> $crate::registry::Registry {
+            $crate::registry::Registry::new(vec![
+                $(Box::new($module::Problem {}),)*
+            ])
+        }
+    };
+}
+
+/// Object-safe counterpart of `Solver`, so that days with different input and output
+/// types can be stored together as `Box<dyn DynSolver>`.
+///
+/// Every `Solver` implements it; answers are returned as [`Answer`]s. Days are unwind safe
+/// so that a panicking day can be caught and reported by the runner.
+pub trait DynSolver: RefUnwindSafe {
+    fn day(&self) -> i32;
+    fn input_file(&self) -> String;
+    /// The name and default value of each of the day's parameters.
+    fn params(&self) -> Vec<(&'static str, String)>;
+    /// Parses `r` and solves both parts with the default parameters.
+    fn solve_from_reader(&self, r: &mut dyn Read) -> Result<(Answer, Answer), Error>;
+    /// Parses `r` and solves `parts` with `overrides` applied, as for an example file.
+    fn solve_example(
+        &self,
+        r: &mut dyn Read,
+        parts: &[Part],
+        overrides: &Overrides,
+    ) -> Result<Vec<Answer>, Error>;
+    fn run(
+        &self,
+        source: &InputSource,
+        parts: &[Part],
+        overrides: &Overrides,
+    ) -> Result<Solution, Error>;
+    fn bench(
+        &self,
+        options: &BenchOptions,
+        source: &InputSource,
+        overrides: &Overrides,
+    ) -> Result<DayBench, Error>;
+}
+
+fn params<S: Solver>(overrides: &Overrides) -> Result<S::Params, Error> {
+    S::Params::with_overrides(overrides).map_err(Error::Param)
+}
+
+impl<S: Solver + RefUnwindSafe> DynSolver for S {
+    fn day(&self) -> i32 {
+        self.get_day()
+    }
+
+    fn input_file(&self) -> String {
+        Solver::input_file(self)
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        S::Params::default().values()
+    }
+
+    fn solve_from_reader(&self, r: &mut dyn Read) -> Result<(Answer, Answer), Error> {
+        let (first, second) = Solver::solve(self, r)?;
+        Ok((first.into(), second.into()))
+    }
+
+    fn solve_example(
+        &self,
+        r: &mut dyn Read,
+        parts: &[Part],
+        overrides: &Overrides,
+    ) -> Result<Vec<Answer>, Error> {
+        let params = params::<S>(overrides)?;
+        let input = self.parse_input(r).map_err(|e| e.for_day(self.get_day()))?;
+        Ok(parts
+            .iter()
+            .map(|&part| self.solve_part(&input, part, &params))
+            .collect())
+    }
+
+    fn run(
+        &self,
+        source: &InputSource,
+        parts: &[Part],
+        overrides: &Overrides,
+    ) -> Result<Solution, Error> {
+        Solver::run(self, source, parts, &params::<S>(overrides)?)
+    }
+
+    fn bench(
+        &self,
+        options: &BenchOptions,
+        source: &InputSource,
+        overrides: &Overrides,
+    ) -> Result<DayBench, Error> {
+        bench::bench(self, options, source, &params::<S>(overrides)?)
+    }
+}
+
+pub struct Registry {
+    days: Vec<Box<dyn DynSolver>>,
+}
+
+impl Registry {
+    pub fn new(mut days: Vec<Box<dyn DynSolver>>) -> Self {
+        days.sort_by_key(|entry| entry.day());
+        if let Some(pair) = days.windows(2).find(|pair| pair[0].day() == pair[1].day()) {
+            panic!("Day {} is registered more than once", pair[0].day());
+        }
+        Self { days }
+    }
+
+    pub fn get(&self, day: i32) -> Option<&dyn DynSolver> {
+        self.days
+            .binary_search_by_key(&day, |entry| entry.day())
+            .ok()
+            .map(|ind| &*self.days[ind])
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &dyn DynSolver> {
+        self.days.iter().map(|entry| &**entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+    use crate::solver::read_string;
+    use std::io;
+
+    struct Echo(i32);
+
+    impl Solver for Echo {
+        type Input = String;
+        type Params = ();
+        type Output1 = String;
+        type Output2 = usize;
+
+        fn get_day(&self) -> i32 {
+            self.0
+        }
+
+        fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+            read_string(r)
+        }
+
+        fn solve_first(&self, input: &Self::Input, _params: &()) -> Self::Output1 {
+            input.trim().to_string()
+        }
+
+        fn solve_second(&self, input: &Self::Input, _params: &()) -> Self::Output2 {
+            input.len()
+        }
+    }
+
+    fn day(day: i32) -> Box<dyn DynSolver> {
+        Box::new(Echo(day))
+    }
+
+    #[test]
+    fn test_get() {
+        let registry = Registry::new(vec![day(3), day(1)]);
+        assert_eq!(registry.get(1).map(|entry| entry.day()), Some(1));
+        assert_eq!(registry.get(3).map(|entry| entry.day()), Some(3));
+        assert!(registry.get(2).is_none());
+        assert_eq!(
+            registry.days().map(|entry| entry.day()).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Day 1 is registered more than once")]
+    fn test_duplicate_day() {
+        Registry::new(vec![day(1), day(1)]);
+    }
+
+    #[test]
+    fn test_solve_from_reader() {
+        let entry = day(7);
+        assert_eq!(entry.input_file(), "input/day07");
+        assert_eq!(
+            entry.solve_from_reader(&mut &b"abc\n"[..]).unwrap(),
+            (Answer::from("abc"), Answer::Int(4))
+        );
+    }
+
+    #[test]
+    fn test_unknown_param() {
+        let entry = day(7);
+        assert!(entry.params().is_empty());
+        let overrides = vec![("preamble".to_string(), "5".to_string())]
+            .into_iter()
+            .collect();
+        let result = entry.solve_example(&mut &b"abc\n"[..], &[Part::One], &overrides);
+        assert_eq!(
+            result.map_err(|e| e.to_string()),
+            Err("unknown parameter \"preamble\"".to_string())
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::alloc::{AllocStats, Bytes};
+use crate::input::InputSource;
+use crate::registry::{DynSolver, Registry};
+use crate::solver::{Overrides, Part, Solution};
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::Display;
+use std::panic::{self, UnwindSafe};
+use std::sync::Once;
+use std::time::Duration;
+
+pub enum Outcome {
+    Solved(Solution),
+    Failed(String),
+}
+
+pub struct Report {
+    pub day: i32,
+    pub outcome: Outcome,
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Calls `f`, turning both its error and any panic into an error message.
+pub fn guard<T, E, F>(f: F) -> Result<T, String>
+where
+    E: Display,
+    F: FnOnce() -> Result<T, E> + UnwindSafe,
+{
+    match panic::catch_unwind(f) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(payload) => Err(format!("panicked: {}", panic_message(payload))),
+    }
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Puts back the thread's previous quiet setting, even when unwinding.
+struct RestoreQuiet(bool);
+
+impl Drop for RestoreQuiet {
+    fn drop(&mut self) {
+        QUIET.with(|quiet| quiet.set(self.0));
+    }
+}
+
+/// Calls `f` with panics on this thread kept out of the panic hook, so that panics caught
+/// by `guard` do not print a backtrace in the middle of the output.
+///
+/// The hook is replaced only once, by one that checks a thread-local flag, so calls on
+/// other threads neither silence this one nor put a stale hook back.
+pub fn quiet_panics<T, F: FnOnce() -> T>(f: F) -> T {
+    INSTALL_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+    let _restore = RestoreQuiet(QUIET.with(|quiet| quiet.replace(true)));
+    f()
+}
+
+pub fn run_day(
+    entry: &dyn DynSolver,
+    source: &InputSource,
+    parts: &[Part],
+    overrides: &Overrides,
+) -> Report {
+    let outcome = match guard(|| entry.run(source, parts, overrides)) {
+        Ok(solution) => Outcome::Solved(solution),
+        Err(message) => Outcome::Failed(message),
+    };
+    Report {
+        day: entry.day(),
+        outcome,
+    }
+}
+
+/// Runs every registered day in order, turning a failing day into a failed report
+/// instead of aborting the whole run.
+pub fn run_all(registry: &Registry, source: &InputSource, parts: &[Part]) -> Vec<Report> {
+    quiet_panics(|| {
+        registry
+            .days()
+            .map(|entry| run_day(entry, source, parts, &Overrides::new()))
+            .collect()
+    })
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.2?}", d)
+}
+
+/// The answer and time columns for `part`, or dashes when it was not run.
+fn part_columns(solution: &Solution, part: Part) -> (String, String) {
+    match solution.get(part) {
+        Some(solution) => (solution.answer.to_string(), format_duration(solution.time)),
+        None => ("-".to_string(), "-".to_string()),
+    }
+}
+
+pub fn print_table(reports: &[Report]) {
+    println!(
+        "{:>3} | {:>20} | {:>10} | {:>20} | {:>10}",
+        "Day", "Part 1", "Time", "Part 2", "Time"
+    );
+    println!("{}", "-".repeat(75));
+    for Report { day, outcome } in reports {
+        match outcome {
+            Outcome::Solved(solution) => {
+                let (first, first_time) = part_columns(solution, Part::One);
+                let (second, second_time) = part_columns(solution, Part::Two);
+                println!(
+                    "{:>3} | {:>20} | {:>10} | {:>20} | {:>10}",
+                    day, first, first_time, second, second_time
+                )
+            }
+            Outcome::Failed(message) => println!("{:>3} | error: {}", day, message),
+        }
+    }
+
+    let total: Duration = reports
+        .iter()
+        .filter_map(|report| match &report.outcome {
+            Outcome::Solved(solution) => {
+                Some(solution.parts.iter().map(|s| s.time).sum::<Duration>())
+            }
+            Outcome::Failed(_) => None,
+        })
+        .sum();
+    println!("{}", "-".repeat(75));
+    println!("Total time: {}", format_duration(total));
+    print_alloc_table(reports);
+}
+
+/// The allocation counts of each phase of the solved days, when they were counted.
+fn alloc_rows(reports: &[Report]) -> Vec<(i32, String, AllocStats)> {
+    let mut rows = Vec::new();
+    for report in reports {
+        if let Outcome::Solved(solution) = &report.outcome {
+            if let Some(stats) = solution.parse_alloc {
+                rows.push((report.day, "parse".to_string(), stats));
+            }
+            for part in solution.parts.iter() {
+                if let Some(stats) = part.alloc {
+                    rows.push((report.day, format!("part {}", part.part), stats));
+                }
+            }
+        }
+    }
+    rows
+}
+
+fn print_alloc_table(reports: &[Report]) {
+    let rows = alloc_rows(reports);
+    if rows.is_empty() {
+        return;
+    }
+    println!();
+    println!(
+        "{:>3} | {:<6} | {:>12} | {:>12} | {:>12}",
+        "Day", "Phase", "Allocations", "Bytes", "Peak"
+    );
+    println!("{}", "-".repeat(57));
+    for (day, phase, stats) in rows {
+        println!(
+            "{:>3} | {:<6} | {:>12} | {:>12} | {:>12}",
+            day,
+            phase,
+            stats.allocations,
+            Bytes(stats.bytes),
+            Bytes(stats.peak)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+    use crate::error::ParseError;
+    use crate::solver::{read_string, Solver};
+    use std::io;
+
+    /// Day 1 solves, day 2 fails to parse and day 3 panics while solving.
+    struct Fake(i32);
+
+    impl Solver for Fake {
+        type Input = String;
+        type Params = ();
+        type Output1 = usize;
+        type Output2 = i32;
+
+        fn get_day(&self) -> i32 {
+            self.0
+        }
+
+        fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+            match self.0 {
+                2 => Err(ParseError::new("bad input")),
+                _ => read_string(r),
+            }
+        }
+
+        fn solve_first(&self, input: &Self::Input, _params: &()) -> Self::Output1 {
+            input.len()
+        }
+
+        fn solve_second(&self, _input: &Self::Input, _params: &()) -> Self::Output2 {
+            match self.0 {
+                3 => panic!("bad input"),
+                day => day,
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_all_reports_failures() {
+        let registry = Registry::new(vec![
+            Box::new(Fake(1)),
+            Box::new(Fake(2)),
+            Box::new(Fake(3)),
+        ]);
+        let source = InputSource::File("Cargo.toml".into());
+        let reports = run_all(&registry, &source, &[Part::Two]);
+
+        assert_eq!(reports.len(), 3);
+        assert!(
+            matches!(&reports[0].outcome, Outcome::Solved(s) if s.parts.len() == 1 && s.get(Part::Two).unwrap().answer == Answer::Int(1))
+        );
+        assert!(matches!(&reports[1].outcome, Outcome::Failed(_)));
+        assert!(matches!(&reports[2].outcome, Outcome::Failed(m) if m == "panicked: bad input"));
+        let phases: Vec<_> = alloc_rows(&reports)
+            .into_iter()
+            .map(|(day, phase, _)| (day, phase))
+            .collect();
+        assert_eq!(
+            phases,
+            vec![(1, "parse".to_string()), (1, "part 2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_quiet_panics() {
+        let quiet = || QUIET.with(Cell::get);
+        let caught = panic::catch_unwind(|| quiet_panics(|| panic!("silent")));
+        assert!(caught.is_err());
+        assert!(!quiet());
+        quiet_panics(|| {
+            quiet_panics(|| assert!(quiet()));
+            assert!(quiet());
+            assert!(!std::thread::spawn(quiet).join().unwrap());
+        });
+        assert!(!quiet());
+    }
+}
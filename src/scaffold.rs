@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEMPLATE: &str = include_str!("template.rs");
+const GET_DAY: &str = "fn get_day(&self) -> i32 {\n        0\n    }";
+
+fn module_name(day: i32) -> String {
+    format!("day{:02}", day)
+}
+
+/// Returns the template with `get_day` returning `day`.
+fn day_source(template: &str, day: i32) -> Result<String, String> {
+    if !template.contains(GET_DAY) {
+        return Err("template.rs no longer has a `get_day` returning 0".to_string());
+    }
+    Ok(template.replacen(
+        GET_DAY,
+        &format!("fn get_day(&self) -> i32 {{\n        {}\n    }}", day),
+        1,
+    ))
+}
+
+/// Declares `module` in `lib` among the other `pub mod dayNN;` lines, keeping them sorted.
+fn declare_module(lib: &str, module: &str) -> Result<String, String> {
+    let mut days = Vec::new();
+    let mut start = 0;
+    for line in lib.split_inclusive('\n') {
+        let name = line
+            .trim_end()
+            .strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(';'));
+        if let Some(name) = name.filter(|name| name.starts_with("day")) {
+            days.push((name, start, start + line.len()));
+        }
+        start += line.len();
+    }
+    if days.iter().any(|&(name, _, _)| name == module) {
+        return Err(format!("{} is already declared in lib.rs", module));
+    }
+    let at = match days.iter().rev().find(|&&(name, _, _)| name < module) {
+        Some(&(_, _, end)) => end,
+        None => match days.first() {
+            Some(&(_, start, _)) => start,
+            None => return Err("Unable to find the day modules in lib.rs".to_string()),
+        },
+    };
+    Ok(format!("{}pub mod {};\n{}", &lib[..at], module, &lib[at..]))
+}
+
+/// Creates `src/dayNN.rs` from the template and an empty `input/dayNN` under `root`, and
+/// declares the module in `src/lib.rs`, which registers it. Returns the files written.
+pub fn new_day(root: &Path, day: i32) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("Invalid day: {}", day));
+    }
+    let module = module_name(day);
+    let source_file = root.join("src").join(format!("{}.rs", module));
+    let input_file = root.join("input").join(&module);
+    let lib_file = root.join("src").join("lib.rs");
+    if source_file.exists() {
+        return Err(format!("{} already exists", source_file.display()));
+    }
+
+    let read_error = |path: &Path, e| format!("Unable to read {}: {}", path.display(), e);
+    let write_error = |path: &Path, e| format!("Unable to write {}: {}", path.display(), e);
+    let lib = fs::read_to_string(&lib_file).map_err(|e| read_error(&lib_file, e))?;
+    let lib = declare_module(&lib, &module)?;
+    let source = day_source(TEMPLATE, day)?;
+
+    fs::write(&source_file, source).map_err(|e| write_error(&source_file, e))?;
+    fs::write(&lib_file, lib).map_err(|e| write_error(&lib_file, e))?;
+    let mut written = vec![source_file, lib_file];
+    if !input_file.exists() {
+        fs::create_dir_all(root.join("input")).map_err(|e| write_error(&input_file, e))?;
+        fs::write(&input_file, "").map_err(|e| write_error(&input_file, e))?;
+        written.push(input_file);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "pub mod solver;
+
+pub mod day01;
+pub mod day10;
+pub mod day12;
+
+include!(concat!(env!(\"OUT_DIR\"), \"/days.rs\"));
+";
+
+    #[test]
+    fn test_day_source() {
+        let source = day_source(TEMPLATE, 17).unwrap();
+        assert!(source.contains("fn get_day(&self) -> i32 {\n        17\n    }"));
+        assert!(day_source("", 17).is_err());
+    }
+
+    #[test]
+    fn test_declare_module() {
+        let lib = declare_module(LIB, "day11").unwrap();
+        assert!(lib.contains("pub mod day10;\npub mod day11;\npub mod day12;\n"));
+        let lib = declare_module(LIB, "day00").unwrap();
+        assert!(lib.contains("\npub mod day00;\npub mod day01;\n"));
+        let lib = declare_module(LIB, "day25").unwrap();
+        assert!(lib.contains("pub mod day12;\npub mod day25;\n\n"));
+        assert!(declare_module(LIB, "day12").is_err());
+        assert!(declare_module("pub mod solver;\n", "day12").is_err());
+    }
+
+    #[test]
+    fn test_new_day_refuses_to_overwrite() {
+        let root = std::env::temp_dir().join(format!("aoc2020_new_day_{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("lib.rs"), LIB).unwrap();
+
+        let written = new_day(&root, 11);
+        let second = new_day(&root, 11);
+        let lib = fs::read_to_string(root.join("src").join("lib.rs")).unwrap();
+        let input = fs::read_to_string(root.join("input").join("day11"));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(written.unwrap().len(), 3);
+        assert!(lib.contains("pub mod day10;\npub mod day11;\npub mod day12;\n"));
+        assert_eq!(input.unwrap(), "");
+        assert!(second.is_err());
+        assert!(new_day(&root, 26).is_err());
+    }
+}
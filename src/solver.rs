@@ -1,43 +1,257 @@
+use crate::alloc::{self, AllocStats};
+use crate::answer::Answer;
+use crate::error::{Error, ParseError};
+use crate::input::InputSource;
+use crate::trace::{self, Event};
 use std::{
-    fmt::Display,
-    fs::File,
+    collections::BTreeMap,
+    fmt::{self, Display},
     io::{self, BufRead, BufReader},
-    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
 };
 
-#[allow(dead_code)]
-pub fn read_to_vec<R: io::Read>(r: R) -> Vec<String> {
+pub fn read_lines<R: io::Read>(r: R) -> Result<Vec<String>, ParseError> {
     let r = BufReader::new(r);
-    r.lines().filter_map(|l| l.ok()).collect()
+    r.lines()
+        .enumerate()
+        .map(|(ind, line)| line.map_err(|e| ParseError::from(e).at_line(ind + 1)))
+        .collect()
+}
+
+pub fn read_string<R: io::Read>(mut r: R) -> Result<String, ParseError> {
+    let mut buf = String::new();
+    r.read_to_string(&mut buf)?;
+    Ok(buf)
+}
+
+/// Parses each non-blank line with `f`, tagging errors with the line number and text.
+pub fn parse_lines<R, T, F>(r: R, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    R: io::Read,
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    read_lines(r)?
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ind, line)| f(line).map_err(|e| e.in_line(ind + 1, line)))
+        .collect()
+}
+
+pub fn parse_num<T: FromStr>(s: &str) -> Result<T, ParseError> {
+    s.trim()
+        .parse()
+        .map_err(|_| ParseError::new(format!("invalid number {:?}", s)))
+}
+
+/// Parameter overrides by name, as given on the command line or in an example file.
+pub type Overrides = BTreeMap<String, String>;
+
+/// A day's tunable puzzle constants, such as day 9's preamble length.
+///
+/// Defaults are the puzzle's own values; any of them can be overridden by name. Declared
+/// with `day_params!`, except for days without parameters, which use `()`.
+pub trait DayParams: Default {
+    /// The name and value of each parameter, in declaration order.
+    fn values(&self) -> Vec<(&'static str, String)>;
+    /// Sets parameter `name` from its text form.
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String>;
+
+    /// Returns the defaults with `overrides` applied.
+    fn with_overrides(overrides: &Overrides) -> Result<Self, String> {
+        let mut params = Self::default();
+        for (name, value) in overrides {
+            params.set(name, value)?;
+        }
+        Ok(params)
+    }
+}
+
+impl DayParams for () {
+    fn values(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    fn set(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(unknown_param(name))
+    }
+}
+
+pub fn unknown_param(name: &str) -> String {
+    format!("unknown parameter {:?}", name)
+}
+
+pub fn parse_param<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {:?}", name, value))
+}
+
+/// Declares a day's `Params` struct from `name: Type = default` fields. Field types are
+/// parsed with `FromStr` and shown with `Display`, and the field names are the names
+/// used to override them.
+macro_rules! day_params {
+    ($($(#[$attr:meta])* $name:ident: $ty:ty = $default:expr),* $(,)?) => {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct Params {
+            $($(#[$attr])* pub $name: $ty,)*
+        }
+
+        impl Default for Params {
+            fn default() -> Self {
+                Self {
+                    $($name: $default,)*
+                }
+            }
+        }
+
+        impl $crate::solver::DayParams for Params {
+            fn values(&self) -> Vec<(&'static str, String)> {
+                vec![$((stringify!($name), self.$name.to_string()),)*]
+            }
+
+            fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+                match name {
+                    $(stringify!($name) => self.$name = $crate::solver::parse_param(name, value)?,)*
+                    _ => return Err($crate::solver::unknown_param(name)),
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+pub fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One = 1,
+    Two = 2,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::One),
+            "2" => Ok(Self::Two),
+            _ => Err(format!("invalid part {:?}", s)),
+        }
+    }
+}
+
+pub struct PartSolution {
+    pub part: Part,
+    pub answer: Answer,
+    pub time: Duration,
+    /// Allocations made solving the part, when they are counted.
+    pub alloc: Option<AllocStats>,
+    /// Events recorded solving the part, when explaining.
+    pub events: Vec<Event>,
+}
+
+pub struct Solution {
+    /// Time spent reading and parsing the input.
+    pub parse_time: Duration,
+    pub parse_alloc: Option<AllocStats>,
+    pub parse_events: Vec<Event>,
+    pub parts: Vec<PartSolution>,
+}
+
+impl Solution {
+    pub fn get(&self, part: Part) -> Option<&PartSolution> {
+        self.parts.iter().find(|solution| solution.part == part)
+    }
 }
 
 pub trait Solver {
     type Input;
-    type Output1: Display;
-    type Output2: Display;
+    /// `()` for days without parameters.
+    type Params: DayParams;
+    type Output1: Into<Answer>;
+    type Output2: Into<Answer>;
 
     fn get_day(&self) -> i32;
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input;
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1;
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2;
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError>;
+    fn solve_first(&self, input: &Self::Input, params: &Self::Params) -> Self::Output1;
+    fn solve_second(&self, input: &Self::Input, params: &Self::Params) -> Self::Output2;
 
     fn input_file(&self) -> String {
         format!("input/day{:02}", self.get_day())
     }
 
-    fn load_input<P: AsRef<Path>>(&self, p: P) -> io::Result<Self::Input> {
-        let f = File::open(p)?;
-        Ok(self.parse_input(f))
+    fn load_input(&self, source: &InputSource) -> Result<Self::Input, Error> {
+        let r = source.open(&self.input_file())?;
+        let input = self.parse_input(r).map_err(|e| e.for_day(self.get_day()))?;
+        Ok(input)
+    }
+
+    fn solve_part(&self, input: &Self::Input, part: Part, params: &Self::Params) -> Answer {
+        match part {
+            Part::One => self.solve_first(input, params).into(),
+            Part::Two => self.solve_second(input, params).into(),
+        }
+    }
+
+    /// Parses `r` and returns both answers with the default parameters.
+    fn solve<R: io::Read>(&self, r: R) -> Result<(Self::Output1, Self::Output2), Error> {
+        let input = self.parse_input(r).map_err(|e| e.for_day(self.get_day()))?;
+        let params = Self::Params::default();
+        Ok((
+            self.solve_first(&input, &params),
+            self.solve_second(&input, &params),
+        ))
     }
 
-    fn solve(&self) {
-        let input_file = self.input_file();
-        let input = self
-            .load_input(input_file)
-            .expect("unable to open input file");
-        let s1 = self.solve_first(&input);
-        let s2 = self.solve_second(&input);
-        println!("Solution 1: {}", s1);
-        println!("Solution 2: {}", s2);
+    fn run(
+        &self,
+        source: &InputSource,
+        parts: &[Part],
+        params: &Self::Params,
+    ) -> Result<Solution, Error> {
+        let (((input, parse_events), parse_alloc), parse_time) =
+            timed(|| alloc::measure(|| trace::capture(|| self.load_input(source))));
+        let input = input?;
+        let parts = parts
+            .iter()
+            .map(|&part| {
+                let (((answer, events), alloc), time) = timed(|| {
+                    alloc::measure(|| trace::capture(|| self.solve_part(&input, part, params)))
+                });
+                PartSolution {
+                    part,
+                    answer,
+                    time,
+                    alloc,
+                    events,
+                }
+            })
+            .collect();
+        Ok(Solution {
+            parse_time,
+            parse_alloc,
+            parse_events,
+            parts,
+        })
     }
 }
@@ -0,0 +1,258 @@
+use crate::input;
+use crate::solver::Part;
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const SUBMISSIONS_FILE: &str = "submissions.txt";
+
+/// What the website said about a submitted answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// An answer was sent too recently; holds the wait the page asks for, if it gave one.
+    RateLimited(Option<String>),
+    /// The part is already solved or not unlocked yet.
+    WrongLevel,
+    Unknown,
+}
+
+impl Response {
+    /// Reads the verdict out of the HTML page returned for a submission.
+    pub fn parse(page: &str) -> Self {
+        if page.contains("That's the right answer") {
+            Self::Right
+        } else if page.contains("That's not the right answer") {
+            if page.contains("your answer is too high") {
+                Self::TooHigh
+            } else if page.contains("your answer is too low") {
+                Self::TooLow
+            } else {
+                Self::Wrong
+            }
+        } else if page.contains("You gave an answer too recently") {
+            let wait = Regex::new(r"You have ([^.]+?) left to wait").unwrap();
+            Self::RateLimited(wait.captures(page).map(|captures| captures[1].to_string()))
+        } else if page.contains("You don't seem to be solving the right level") {
+            Self::WrongLevel
+        } else {
+            Self::Unknown
+        }
+    }
+
+    fn token(&self) -> &'static str {
+        match self {
+            Self::Right => "right",
+            Self::Wrong => "wrong",
+            Self::TooHigh => "too-high",
+            Self::TooLow => "too-low",
+            Self::RateLimited(_) => "rate-limited",
+            Self::WrongLevel => "wrong-level",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        Some(match token {
+            "right" => Self::Right,
+            "wrong" => Self::Wrong,
+            "too-high" => Self::TooHigh,
+            "too-low" => Self::TooLow,
+            "rate-limited" => Self::RateLimited(None),
+            "wrong-level" => Self::WrongLevel,
+            "unknown" => Self::Unknown,
+            _ => return None,
+        })
+    }
+
+    /// Whether the answer was judged wrong, so that sending it again is pointless.
+    fn is_wrong(&self) -> bool {
+        matches!(self, Self::Wrong | Self::TooHigh | Self::TooLow)
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Right => write!(f, "right answer"),
+            Self::Wrong => write!(f, "wrong answer"),
+            Self::TooHigh => write!(f, "wrong answer, too high"),
+            Self::TooLow => write!(f, "wrong answer, too low"),
+            Self::RateLimited(Some(wait)) => write!(f, "rate limited, wait {}", wait),
+            Self::RateLimited(None) => write!(f, "rate limited"),
+            Self::WrongLevel => write!(f, "part already solved or still locked"),
+            Self::Unknown => write!(f, "unrecognized response"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Submission {
+    pub day: i32,
+    pub part: Part,
+    pub response: Response,
+    pub answer: String,
+}
+
+/// Every answer sent so far, stored as `<day> <part> <response> <answer>` lines.
+#[derive(Default)]
+pub struct Submissions {
+    entries: Vec<Submission>,
+}
+
+impl Submissions {
+    pub fn load<P: AsRef<Path>>(p: P) -> io::Result<Self> {
+        let entries = input::load_lines(p, "submission entry", |line| {
+            let fields: Vec<_> = line.splitn(4, ' ').collect();
+            match fields[..] {
+                [day, part, response, answer] => Some(Submission {
+                    day: day.parse().ok()?,
+                    part: part.parse().ok()?,
+                    response: Response::from_token(response)?,
+                    answer: answer.to_string(),
+                }),
+                _ => None,
+            }
+        })?;
+        Ok(Self { entries })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, p: P) -> io::Result<()> {
+        let content: String = self
+            .entries
+            .iter()
+            .map(|s| format!("{} {} {} {}\n", s.day, s.part, s.response.token(), s.answer))
+            .collect();
+        fs::write(p, content)
+    }
+
+    pub fn record(&mut self, day: i32, part: Part, answer: &str, response: Response) {
+        self.entries.push(Submission {
+            day,
+            part,
+            response,
+            answer: answer.to_string(),
+        });
+    }
+
+    /// Refuses an answer that cannot be right given earlier responses: the part is already
+    /// solved, the answer was judged wrong, or it is outside a known too-high or too-low
+    /// bound.
+    pub fn check(&self, day: i32, part: Part, answer: &str) -> Result<(), String> {
+        let earlier = self
+            .entries
+            .iter()
+            .filter(|s| s.day == day && s.part == part);
+        let number = answer.parse::<i128>().ok();
+        for submission in earlier {
+            let bound = submission.answer.parse::<i128>().ok();
+            let refused = match (&submission.response, number, bound) {
+                (Response::Right, _, _) => true,
+                (response, _, _) if response.is_wrong() && submission.answer == answer => true,
+                (Response::TooHigh, Some(number), Some(bound)) => number >= bound,
+                (Response::TooLow, Some(number), Some(bound)) => number <= bound,
+                _ => false,
+            };
+            if refused {
+                return Err(format!(
+                    "Not sending {} for day {} part {}: {} was a {}",
+                    answer, day, part, submission.answer, submission.response
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let page = |text: &str| format!("<main>\n<article><p>{}</p></article>\n</main>", text);
+        assert_eq!(
+            Response::parse(&page(
+                "That's the right answer! You are one gold star closer."
+            )),
+            Response::Right
+        );
+        assert_eq!(
+            Response::parse(&page("That's not the right answer. If you're stuck, ...")),
+            Response::Wrong
+        );
+        assert_eq!(
+            Response::parse(&page(
+                "That's not the right answer; your answer is too high."
+            )),
+            Response::TooHigh
+        );
+        assert_eq!(
+            Response::parse(&page(
+                "That's not the right answer; your answer is too low."
+            )),
+            Response::TooLow
+        );
+        assert_eq!(
+            Response::parse(&page(
+                "You gave an answer too recently. You have 43s left to wait."
+            )),
+            Response::RateLimited(Some("43s".to_string()))
+        );
+        assert_eq!(
+            Response::parse(&page("You don't seem to be solving the right level.")),
+            Response::WrongLevel
+        );
+        assert_eq!(Response::parse("<html></html>"), Response::Unknown);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut submissions = Submissions::default();
+        submissions.record(1, Part::One, "500", Response::TooHigh);
+        submissions.record(1, Part::One, "100", Response::TooLow);
+        submissions.record(1, Part::One, "300", Response::Wrong);
+        submissions.record(1, Part::One, "250", Response::RateLimited(None));
+
+        assert!(submissions.check(1, Part::One, "500").is_err());
+        assert!(submissions.check(1, Part::One, "700").is_err());
+        assert!(submissions.check(1, Part::One, "100").is_err());
+        assert!(submissions.check(1, Part::One, "-3").is_err());
+        assert!(submissions.check(1, Part::One, "300").is_err());
+        assert!(submissions.check(1, Part::One, "250").is_ok());
+        assert!(submissions.check(1, Part::One, "499").is_ok());
+        assert!(submissions.check(1, Part::Two, "700").is_ok());
+        assert!(submissions.check(2, Part::One, "700").is_ok());
+
+        submissions.record(1, Part::One, "250", Response::Right);
+        assert!(submissions.check(1, Part::One, "499").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join(format!("aoc2020_submissions_{}", std::process::id()));
+        let mut submissions = Submissions::default();
+        submissions.record(16, Part::Two, "a,b c", Response::TooLow);
+        submissions.record(
+            1,
+            Part::One,
+            "42",
+            Response::RateLimited(Some("1m".to_string())),
+        );
+        submissions.save(&path).unwrap();
+        let loaded = Submissions::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.entries[0], submissions.entries[0]);
+        assert_eq!(loaded.entries[1].response, Response::RateLimited(None));
+        assert!(Submissions::load("does/not/exist")
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+}
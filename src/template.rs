@@ -1,28 +1,30 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::answer::Answer;
+use crate::error::ParseError;
+use crate::solver::{parse_lines, parse_num, Solver};
+use std::io;
 
 pub struct Problem;
 
 impl Solver for Problem {
     type Input = Vec<i32>;
-    type Output1 = i32;
-    type Output2 = i32;
+    type Params = ();
+    type Output1 = Answer;
+    type Output2 = Answer;
 
     fn get_day(&self) -> i32 {
         0
     }
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        vec![0]
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(r, parse_num)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        0
+    fn solve_first(&self, _input: &Self::Input, _params: &Self::Params) -> Self::Output1 {
+        Answer::NotImplemented
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        0
+    fn solve_second(&self, _input: &Self::Input, _params: &Self::Params) -> Self::Output2 {
+        Answer::NotImplemented
     }
 }
 
@@ -34,15 +36,15 @@ mod tests {
     fn test_first() {
         let raw_input = "";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_first(&input), 0);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_first(&input, &()), Answer::NotImplemented);
     }
 
     #[test]
     fn test_second() {
         let raw_input = "";
         let problem = Problem {};
-        let input = problem.parse_input(raw_input.as_bytes());
-        assert_eq!(problem.solve_second(&input), 0);
+        let input = problem.parse_input(raw_input.as_bytes()).unwrap();
+        assert_eq!(problem.solve_second(&input, &()), Answer::NotImplemented);
     }
 }
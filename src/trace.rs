@@ -0,0 +1,109 @@
+//! Step-by-step explanations of how an answer was found.
+//!
+//! Solvers record [`Event`]s with the `trace!` macro, such as the pair of entries that
+//! sums to the target. Events are only kept inside [`explain`]; everywhere else `trace!`
+//! is a thread-local check that skips building the event.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+/// One step of a derivation: a name and the values involved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        for (key, value) in self.fields.iter() {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether events are being kept on this thread.
+pub fn enabled() -> bool {
+    ENABLED.with(Cell::get)
+}
+
+/// Keeps `event` for the innermost [`capture`], if events are being kept.
+pub fn record(event: Event) {
+    if enabled() {
+        EVENTS.with(|events| events.borrow_mut().push(event));
+    }
+}
+
+/// Runs `f` with events kept on this thread.
+pub fn explain<T, F: FnOnce() -> T>(f: F) -> T {
+    let was_enabled = ENABLED.with(|enabled| enabled.replace(true));
+    let result = f();
+    ENABLED.with(|enabled| enabled.set(was_enabled));
+    result
+}
+
+/// Runs `f` and returns the events it recorded, none outside [`explain`].
+pub fn capture<T, F: FnOnce() -> T>(f: F) -> (T, Vec<Event>) {
+    if !enabled() {
+        return (f(), Vec::new());
+    }
+    let outer = EVENTS.with(|events| events.take());
+    let result = f();
+    let events = EVENTS.with(|events| events.replace(outer));
+    (result, events)
+}
+
+/// Records an event named `$name` with `key = value` fields when explaining. The values
+/// are only evaluated then, and are shown with `Display`.
+macro_rules! trace {
+    ($name:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::trace::enabled() {
+            $crate::trace::record($crate::trace::Event {
+                name: $name,
+                fields: vec![$((stringify!($key), $value.to_string()),)*],
+            });
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture() {
+        let count = Cell::new(0);
+        let step = || {
+            trace!(
+                "step",
+                n = {
+                    count.set(count.get() + 1);
+                    count.get()
+                }
+            );
+        };
+
+        let ((), events) = capture(step);
+        assert!(events.is_empty());
+        assert_eq!(count.get(), 0);
+
+        let (((), inner), outer) = explain(|| {
+            capture(|| {
+                step();
+                capture(step)
+            })
+        });
+        assert_eq!(inner.len(), 1);
+        assert_eq!(outer.len(), 1);
+        assert_eq!(outer[0].to_string(), "step n=1");
+        assert_eq!(inner[0].to_string(), "step n=2");
+        assert!(!enabled());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::answer::Answer;
+use crate::input;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// FNV-1a hash of an input file, used to tell whether a stored answer belongs to it.
+pub fn input_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expected {
+    pub input_hash: u64,
+    pub answer: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong {
+        expected: String,
+    },
+    /// No answer is stored for this day and part.
+    Unknown,
+    /// The stored answer was recorded for a different input.
+    OtherInput,
+    /// The solver found no answer and none is stored.
+    NoSolution,
+    /// The part is not solved yet, so there is nothing to check.
+    NotImplemented,
+}
+
+/// Expected answers keyed by day and part, stored as `<day> <part> <input hash> <answer>`
+/// lines.
+#[derive(Default)]
+pub struct Answers {
+    entries: BTreeMap<(i32, u8), Expected>,
+}
+
+impl Answers {
+    pub fn load<P: AsRef<Path>>(p: P) -> io::Result<Self> {
+        let entries = input::load_lines(p, "answer entry", |line| {
+            let fields: Vec<_> = line.splitn(4, ' ').collect();
+            match fields[..] {
+                [day, part, hash, answer] => {
+                    let input_hash = u64::from_str_radix(hash, 16).ok()?;
+                    let answer = answer.to_string();
+                    Some((
+                        (day.parse().ok()?, part.parse().ok()?),
+                        Expected { input_hash, answer },
+                    ))
+                }
+                _ => None,
+            }
+        })?;
+        Ok(Self {
+            entries: entries.into_iter().collect(),
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, p: P) -> io::Result<()> {
+        let content: String = self
+            .entries
+            .iter()
+            .map(|((day, part), expected)| {
+                format!(
+                    "{} {} {:016x} {}\n",
+                    day, part, expected.input_hash, expected.answer
+                )
+            })
+            .collect();
+        fs::write(p, content)
+    }
+
+    pub fn get(&self, day: i32, part: u8) -> Option<&Expected> {
+        self.entries.get(&(day, part))
+    }
+
+    pub fn record(&mut self, day: i32, part: u8, input_hash: u64, answer: &str) {
+        let answer = answer.to_string();
+        self.entries
+            .insert((day, part), Expected { input_hash, answer });
+    }
+
+    /// Compares `answer` with the stored one. Finding no solution where an answer is
+    /// stored for the same input is a mismatch.
+    pub fn check(&self, day: i32, part: u8, input_hash: u64, answer: &Answer) -> Verdict {
+        let expected = self
+            .get(day, part)
+            .filter(|expected| expected.input_hash == input_hash);
+        match (answer, expected) {
+            (Answer::NotImplemented, _) => Verdict::NotImplemented,
+            (Answer::NoSolution, None) => Verdict::NoSolution,
+            (_, None) if self.get(day, part).is_some() => Verdict::OtherInput,
+            (_, None) => Verdict::Unknown,
+            (answer, Some(expected)) if expected.answer == answer.to_string() => Verdict::Correct,
+            (_, Some(expected)) => Verdict::Wrong {
+                expected: expected.answer.clone(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_hash() {
+        assert_eq!(input_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(input_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(input_hash(b"1\n2\n"), input_hash(b"2\n1\n"));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut answers = Answers::default();
+        let text = Answer::Text("a,b c".to_string());
+        answers.record(16, 2, 42, "a,b c");
+        assert_eq!(answers.check(16, 2, 42, &text), Verdict::Correct);
+        assert_eq!(
+            answers.check(16, 2, 42, &Answer::Int(0)),
+            Verdict::Wrong {
+                expected: "a,b c".to_string()
+            }
+        );
+        assert_eq!(answers.check(16, 2, 7, &text), Verdict::OtherInput);
+        assert_eq!(answers.check(16, 1, 42, &Answer::Int(0)), Verdict::Unknown);
+
+        assert_eq!(
+            answers.check(16, 2, 42, &Answer::NoSolution),
+            Verdict::Wrong {
+                expected: "a,b c".to_string()
+            }
+        );
+        assert_eq!(
+            answers.check(16, 2, 7, &Answer::NoSolution),
+            Verdict::NoSolution
+        );
+        assert_eq!(
+            answers.check(16, 1, 42, &Answer::NoSolution),
+            Verdict::NoSolution
+        );
+        assert_eq!(
+            answers.check(16, 2, 42, &Answer::NotImplemented),
+            Verdict::NotImplemented
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join(format!("aoc2020_answers_{}", std::process::id()));
+        let mut answers = Answers::default();
+        answers.record(1, 1, 0xdead_beef, "996996");
+        answers.record(21, 2, u64::MAX, "mxmxvkd,sqjhc,fvjkl");
+        answers.save(&path).unwrap();
+
+        let loaded = Answers::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get(1, 1), answers.get(1, 1));
+        assert_eq!(loaded.get(21, 2), answers.get(21, 2));
+        assert_eq!(loaded.get(1, 2), None);
+    }
+}
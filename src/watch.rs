@@ -0,0 +1,63 @@
+use crate::verify::input_hash;
+use std::fs;
+use std::path::PathBuf;
+
+/// Polls a set of files for changes to their content.
+pub struct Watch {
+    files: Vec<PathBuf>,
+    /// Hash of each file's content at the last poll; `None` while it can't be read.
+    hashes: Vec<Option<u64>>,
+    polled: bool,
+}
+
+impl Watch {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let hashes = vec![None; files.len()];
+        Self {
+            files,
+            hashes,
+            polled: false,
+        }
+    }
+
+    /// Returns true on the first poll and whenever a file was changed, created or removed
+    /// since the previous one.
+    pub fn poll(&mut self) -> bool {
+        let hashes: Vec<_> = self
+            .files
+            .iter()
+            .map(|file| fs::read(file).ok().map(|bytes| input_hash(&bytes)))
+            .collect();
+        let changed = !self.polled || hashes != self.hashes;
+        self.hashes = hashes;
+        self.polled = true;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll() {
+        let path = std::env::temp_dir().join(format!("aoc2020_watch_{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watch = Watch::new(vec![path.clone()]);
+
+        let initial = watch.poll();
+        let missing = watch.poll();
+        fs::write(&path, "1\n").unwrap();
+        let created = watch.poll();
+        let unchanged = watch.poll();
+        fs::write(&path, "2\n").unwrap();
+        let modified = watch.poll();
+        fs::remove_file(&path).unwrap();
+        let removed = watch.poll();
+
+        assert_eq!(
+            (initial, missing, created, unchanged, modified, removed),
+            (true, false, true, false, true, true)
+        );
+    }
+}